@@ -1,8 +1,11 @@
 /// The intention here is to demonstrate what seems intuitively true:
 /// simple substring search is faster than the regex equivalent. This
 /// test isn't meant to be conclusive, merely suggestive.
-use criterion::{Criterion, criterion_group, criterion_main};
-use maybe_regex::MaybeRegex;
+///
+/// The `corpora` group runs the heavier operations over generated text, a few MB of log lines
+/// and a few thousand short rows, for evaluating performance work on those paths.
+use criterion::{BenchmarkId, Criterion, Throughput, criterion_group, criterion_main};
+use maybe_regex::{MaybeRegex, MaybeRegexSet};
 use regex::Regex;
 use std::hint::black_box;
 
@@ -40,5 +43,115 @@ fn criterion_benchmark(c: &mut Criterion) {
     });
 }
 
+const WORDS: &str = "the quick brown fox Error request timeout user id database connection WARN \
+    retry after failed ok status 200 404 cache";
+
+// Deterministic pseudo-random text, so runs are comparable.
+fn words(count: usize, seed: u64) -> impl Iterator<Item = &'static str> {
+    let words: Vec<_> = WORDS.split_whitespace().collect();
+    let mut state = seed;
+    (0..count).map(move |_| {
+        state = state
+            .wrapping_mul(6364136223846793005)
+            .wrapping_add(1442695040888963407);
+        words[(state >> 33) as usize % words.len()]
+    })
+}
+
+// About 4MB of log-like lines.
+fn large_text() -> String {
+    let mut text = String::with_capacity(4 << 20);
+    for (i, word) in words(700_000, 1).enumerate() {
+        text.push_str(word);
+        text.push(if i % 12 == 11 { '\n' } else { ' ' });
+    }
+    text
+}
+
+fn short_rows() -> Vec<String> {
+    let all: Vec<_> = words(5_000 * 8, 2).collect();
+    all.chunks(8).map(|row| row.join(" ")).collect()
+}
+
+fn replace_benchmark(c: &mut Criterion) {
+    let text = large_text();
+    let mut group = c.benchmark_group("replace");
+    group.throughput(Throughput::Bytes(text.len() as u64));
+    group.sample_size(10);
+    for pattern in ["timeout", "[0-9]{3}"] {
+        let needle = MaybeRegex::new(pattern).as_case_sensitive();
+        group.bench_with_input(BenchmarkId::from_parameter(pattern), &text, |b, text| {
+            b.iter(|| needle.replace(text.clone(), |found| format!("<{found}>")))
+        });
+    }
+    group.finish();
+}
+
+fn match_indices_benchmark(c: &mut Criterion) {
+    let text = large_text();
+    let mut group = c.benchmark_group("match_indices");
+    group.throughput(Throughput::Bytes(text.len() as u64));
+    group.sample_size(10);
+    for pattern in ["database", "retry after", "(fail|retry)[a-z]*"] {
+        let needle = MaybeRegex::new(pattern).as_case_sensitive();
+        group.bench_with_input(BenchmarkId::from_parameter(pattern), &text, |b, text| {
+            b.iter(|| needle.match_indices(black_box(text)))
+        });
+    }
+    group.finish();
+}
+
+// The same patterns with and without lowercasing the haystack.
+fn case_insensitive_benchmark(c: &mut Criterion) {
+    let rows = short_rows();
+    let mut group = c.benchmark_group("case_sensitivity");
+    group.throughput(Throughput::Elements(rows.len() as u64));
+    for pattern in ["error", "warn.*retry"] {
+        let insensitive = MaybeRegex::new(pattern);
+        let sensitive = MaybeRegex::new(pattern).as_case_sensitive();
+        group.bench_with_input(
+            BenchmarkId::new("insensitive", pattern),
+            &rows,
+            |b, rows| b.iter(|| rows.iter().filter(|row| insensitive.matches(row)).count()),
+        );
+        group.bench_with_input(BenchmarkId::new("sensitive", pattern), &rows, |b, rows| {
+            b.iter(|| rows.iter().filter(|row| sensitive.matches(row)).count())
+        });
+    }
+    group.finish();
+}
+
+fn set_benchmark(c: &mut Criterion) {
+    let rows = short_rows();
+    let mut group = c.benchmark_group("set");
+    group.throughput(Throughput::Elements(rows.len() as u64));
+    for size in [4, 32, 256] {
+        let patterns: Vec<String> = words(size, 3)
+            .enumerate()
+            .map(|(i, word)| match i % 4 {
+                0 => format!("{word} [a-z]+"),
+                1 => format!("-{word}{i}"),
+                _ => format!("{word} {word}"),
+            })
+            .collect();
+        let set = MaybeRegexSet::new(&patterns);
+        let uncompiled = set.clone().with_memory_budget(0);
+        group.bench_with_input(BenchmarkId::new("compiled", size), &rows, |b, rows| {
+            b.iter(|| rows.iter().filter(|row| set.matches(row)).count())
+        });
+        group.bench_with_input(BenchmarkId::new("per_pattern", size), &rows, |b, rows| {
+            b.iter(|| rows.iter().filter(|row| uncompiled.matches(row)).count())
+        });
+    }
+    group.finish();
+}
+
 criterion_group!(benches, criterion_benchmark);
-criterion_main!(benches);
+criterion_group!(
+    corpora,
+    replace_benchmark,
+    match_indices_benchmark,
+    case_insensitive_benchmark,
+    set_benchmark
+);
+criterion_main!(benches, corpora);
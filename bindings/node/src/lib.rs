@@ -0,0 +1,100 @@
+use napi::{Env, JsFunction, JsString, Result};
+use napi_derive::napi;
+
+/// A match location. Offsets are in UTF-16 code units so they can be used directly with
+/// JavaScript string methods like `slice`.
+#[napi(object)]
+pub struct Span {
+    pub start: u32,
+    pub length: u32,
+}
+
+#[napi]
+pub struct MaybeRegex {
+    inner: maybe_regex::MaybeRegex,
+}
+
+#[napi]
+impl MaybeRegex {
+    #[napi(constructor)]
+    pub fn new(pattern: String) -> Self {
+        Self {
+            inner: maybe_regex::MaybeRegex::new(pattern),
+        }
+    }
+
+    #[napi(factory)]
+    pub fn case_sensitive(pattern: String) -> Self {
+        Self {
+            inner: maybe_regex::MaybeRegex::new(pattern).as_case_sensitive(),
+        }
+    }
+
+    #[napi(getter)]
+    pub fn is_regex(&self) -> bool {
+        self.inner.is_regex()
+    }
+
+    #[napi(getter)]
+    pub fn is_negative(&self) -> bool {
+        self.inner.is_negative
+    }
+
+    #[napi]
+    pub fn matches(&self, haystack: String) -> bool {
+        self.inner.matches(haystack)
+    }
+
+    #[napi]
+    pub fn is_contained_within(&self, haystack: String) -> bool {
+        self.inner.is_contained_within(haystack)
+    }
+
+    #[napi]
+    pub fn match_indices(&self, haystack: String) -> Vec<Span> {
+        self.inner
+            .match_indices(&haystack)
+            .into_iter()
+            .filter_map(|(start, len)| {
+                let start_utf16 = utf16_len(haystack.get(..start)?);
+                let length = utf16_len(haystack.get(start..start + len)?);
+                Some(Span {
+                    start: start_utf16,
+                    length,
+                })
+            })
+            .collect()
+    }
+
+    /// Replaces every match with the string returned by `callback(matchedText)`.
+    #[napi]
+    pub fn replace(&self, env: Env, haystack: String, callback: JsFunction) -> Result<String> {
+        let mut output = String::with_capacity(haystack.len());
+        let mut last = 0;
+        for (start, len) in self.inner.match_indices(&haystack) {
+            let Some(matched) = haystack.get(start..start + len) else {
+                continue;
+            };
+            if start < last {
+                continue;
+            }
+            output.push_str(&haystack[last..start]);
+            let replacement: JsString = callback
+                .call(None, &[env.create_string(matched)?])?
+                .coerce_to_string()?;
+            output.push_str(replacement.into_utf8()?.as_str()?);
+            last = start + len;
+        }
+        output.push_str(&haystack[last..]);
+        Ok(output)
+    }
+
+    #[napi]
+    pub fn to_string(&self) -> String {
+        self.inner.to_string()
+    }
+}
+
+fn utf16_len(s: &str) -> u32 {
+    s.encode_utf16().count() as u32
+}
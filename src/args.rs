@@ -0,0 +1,84 @@
+use crate::{MaybeRegex, MaybeRegexSet};
+use std::io::{self, BufRead};
+
+impl MaybeRegexSet {
+    /// Builds a set from command-line arguments, following the conventions every CLI using
+    /// this crate should share:
+    ///
+    /// - Each argument is a pattern, so `-foo` is the negative pattern `foo` rather than a flag.
+    ///   Argument parsers that would otherwise reject it can be told to stop at `--`; the first
+    ///   `--` is dropped here.
+    /// - A lone `-` reads more patterns from stdin, as with `from_reader`. After `--` it's
+    ///   skipped like any other empty pattern.
+    pub fn from_args<I: IntoIterator<Item = String>>(args: I) -> io::Result<Self> {
+        from_args_with(args, || io::stdin().lock())
+    }
+
+    /// Reads one pattern per line. Blank lines and lines starting with `#` are skipped; start
+    /// a line with `\#` for a pattern beginning with `#`.
+    pub fn from_reader<R: BufRead>(reader: R) -> io::Result<Self> {
+        let mut set = Self::default();
+        for line in reader.lines() {
+            let line = line?;
+            let line = line.strip_suffix('\r').unwrap_or(&line);
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let line = if line.starts_with("\\#") {
+                &line[1..]
+            } else {
+                line
+            };
+            set.push(MaybeRegex::new(line));
+        }
+        Ok(set)
+    }
+}
+
+fn from_args_with<I, R>(args: I, stdin: impl FnOnce() -> R) -> io::Result<MaybeRegexSet>
+where
+    I: IntoIterator<Item = String>,
+    R: BufRead,
+{
+    let mut set = MaybeRegexSet::default();
+    let mut stdin = Some(stdin);
+    let mut after_separator = false;
+    for arg in args {
+        match arg.as_str() {
+            "--" if !after_separator => after_separator = true,
+            "-" if !after_separator => {
+                // Stdin can only be read once
+                if let Some(stdin) = stdin.take() {
+                    set.extend(MaybeRegexSet::from_reader(stdin())?.iter().cloned());
+                }
+            }
+            "" | "-" => {}
+            _ => set.push(MaybeRegex::new(arg)),
+        }
+    }
+    Ok(set)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn parses_args_and_lines() {
+        let args = ["error", "-", "--", "-debug", "-"].map(String::from);
+        let set = from_args_with(args, || "# comments\n\n^warn\r\n\\#tag\n".as_bytes()).unwrap();
+        let patterns: Vec<_> = set
+            .iter()
+            .map(|pattern| (pattern.to_str(), pattern.is_negative))
+            .collect();
+        assert_eq!(
+            patterns,
+            [
+                ("error", false),
+                ("^warn", false),
+                ("#tag", false),
+                ("debug", true),
+            ]
+        );
+    }
+}
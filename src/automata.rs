@@ -0,0 +1,342 @@
+use crate::{MaybeRegex, TagWrapperData};
+#[cfg(feature = "automata")]
+use regex_automata::{
+    Anchored, Input, MatchKind,
+    dfa::{Automaton, StartKind, dense},
+    nfa::thompson,
+    util::{primitives::StateID, syntax},
+};
+#[cfg(feature = "automata")]
+use std::collections::{HashMap, HashSet, VecDeque};
+
+// Bounds the memory used to build each DFA and the number of state pairs explored.
+#[cfg(feature = "automata")]
+pub(crate) const DEFAULT_SIZE_LIMIT: usize = 1 << 20;
+
+/// Whether two patterns can both match the same haystack.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum OverlapResult {
+    /// Some haystack matches both patterns, e.g. `example`.
+    Overlapping { example: String },
+    /// No haystack can match both patterns.
+    Disjoint,
+    /// The patterns were too large to analyze, or use features (like Unicode word
+    /// boundaries) that can't be compiled into a DFA. Without the `automata` feature, this is
+    /// the answer for anything involving a regex.
+    Unknown,
+}
+
+impl MaybeRegex {
+    /// Checks whether some haystack would satisfy `matches` for both patterns, taking negation
+    /// and case sensitivity into account. Literal pairs are answered directly; anything involving
+    /// a regex is decided by intersecting DFAs with the `automata` feature.
+    pub fn overlaps(&self, other: &MaybeRegex) -> OverlapResult {
+        if let (TagWrapperData::Raw(a), TagWrapperData::Raw(b)) = (&self.data, &other.data) {
+            let candidates = [a.clone(), b.clone(), format!("{a}{b}"), String::new()];
+            if let Some(example) = candidates
+                .into_iter()
+                .find(|candidate| self.matches(candidate) && other.matches(candidate))
+            {
+                return OverlapResult::Overlapping { example };
+            }
+        }
+
+        #[cfg(not(feature = "automata"))]
+        return OverlapResult::Unknown;
+
+        #[cfg(feature = "automata")]
+        match (
+            PatternDfa::new(self, DEFAULT_SIZE_LIMIT),
+            PatternDfa::new(other, DEFAULT_SIZE_LIMIT),
+        ) {
+            (Some(a), Some(b)) => match find_common(&a, &b, DEFAULT_SIZE_LIMIT) {
+                Some(Some(bytes)) => OverlapResult::Overlapping {
+                    example: String::from_utf8_lossy(&bytes).into_owned(),
+                },
+                Some(None) => OverlapResult::Disjoint,
+                None => OverlapResult::Unknown,
+            },
+            _ => OverlapResult::Unknown,
+        }
+    }
+
+    #[cfg(feature = "automata")]
+    /// Whether every haystack matched by this pattern is also matched by `other`, e.g.
+    /// `error: timeout` is a subset of `error.*`. Returns `None` when the patterns are too
+    /// large to analyze within the default size limit.
+    pub fn is_subset_of(&self, other: &MaybeRegex) -> Option<bool> {
+        self.is_subset_of_with_limit(other, DEFAULT_SIZE_LIMIT)
+    }
+
+    #[cfg(feature = "automata")]
+    /// Like `is_subset_of`, but bounding both the DFA sizes (in bytes) and the number of
+    /// states explored by `size_limit`.
+    pub fn is_subset_of_with_limit(&self, other: &MaybeRegex, size_limit: usize) -> Option<bool> {
+        let a = PatternDfa::new(self, size_limit)?;
+        let not_b = PatternDfa::new(other, size_limit)?.complement();
+        find_common(&a, &not_b, size_limit).map(|example| example.is_none())
+    }
+
+    #[cfg(feature = "automata")]
+    /// Whether both patterns match exactly the same haystacks.
+    pub fn is_equivalent_to(&self, other: &MaybeRegex) -> Option<bool> {
+        self.is_equivalent_to_with_limit(other, DEFAULT_SIZE_LIMIT)
+    }
+
+    #[cfg(feature = "automata")]
+    pub fn is_equivalent_to_with_limit(
+        &self,
+        other: &MaybeRegex,
+        size_limit: usize,
+    ) -> Option<bool> {
+        match self.is_subset_of_with_limit(other, size_limit)? {
+            true => other.is_subset_of_with_limit(self, size_limit),
+            false => Some(false),
+        }
+    }
+
+    /// Whether appending more text to `partial_haystack` could still produce a haystack that
+    /// `matches` accepts, for live feedback while users type. This is exact for literals and
+    /// decided by walking the DFA for regexes with the `automata` feature; regexes that can't
+    /// be analyzed report `true`.
+    pub fn could_match<S: AsRef<str>>(&self, partial_haystack: S) -> bool {
+        let partial_haystack = partial_haystack.as_ref();
+        if let TagWrapperData::Raw(_) = &self.data
+            && !self.whole_word
+        {
+            // A needle can always be appended, but once it's present it can't be removed
+            return !self.is_negative || !self.is_contained_within(partial_haystack);
+        }
+
+        #[cfg(not(feature = "automata"))]
+        return true;
+
+        #[cfg(feature = "automata")]
+        {
+            let Some(dfa) = PatternDfa::new(self, DEFAULT_SIZE_LIMIT) else {
+                return true;
+            };
+            let state = partial_haystack
+                .bytes()
+                .fold(dfa.start(), |state, byte| dfa.next(state, byte));
+            dfa.can_accept_from(state, DEFAULT_SIZE_LIMIT)
+                .unwrap_or(true)
+        }
+    }
+
+    // The pattern as regex syntax, plus whether it should be compiled case-insensitively.
+    #[cfg(feature = "automata")]
+    pub(crate) fn automaton_pattern(&self) -> (String, bool) {
+        (self.regex_source().into_owned(), !self.case_sensitive)
+    }
+}
+
+#[cfg(feature = "automata")]
+/// A DFA accepting exactly the haystacks for which `MaybeRegex::matches` is true.
+pub(crate) struct PatternDfa {
+    dfa: dense::DFA<Vec<u32>>,
+    start: StateID,
+    negated: bool,
+}
+
+#[cfg(feature = "automata")]
+impl PatternDfa {
+    pub(crate) fn new(pattern: &MaybeRegex, size_limit: usize) -> Option<Self> {
+        let (regex, case_insensitive) = pattern.automaton_pattern();
+        // Surrounding the pattern with `.*` turns "contains a match" into "matches entirely",
+        // which is what an anchored DFA checks at the end of input. The surrounding text may be
+        // any bytes, otherwise negated DFAs would accept haystacks just for being invalid UTF-8.
+        let wrapped = format!("(?s-u:.*)(?:{regex})(?s-u:.*)");
+        let dfa = dense::Builder::new()
+            .configure(
+                dense::Config::new()
+                    .match_kind(MatchKind::All)
+                    .start_kind(StartKind::Anchored)
+                    .dfa_size_limit(Some(size_limit))
+                    .determinize_size_limit(Some(size_limit)),
+            )
+            .syntax(
+                syntax::Config::new()
+                    .case_insensitive(case_insensitive)
+                    .utf8(false),
+            )
+            .thompson(thompson::Config::new().nfa_size_limit(Some(size_limit)))
+            .build(&wrapped)
+            .ok()?;
+        let start = dfa
+            .start_state_forward(&Input::new("").anchored(Anchored::Yes))
+            .ok()?;
+        Some(Self {
+            dfa,
+            start,
+            negated: pattern.is_negative,
+        })
+    }
+
+    // Accepts exactly the haystacks this DFA rejects.
+    pub(crate) fn complement(mut self) -> Self {
+        self.negated = !self.negated;
+        self
+    }
+
+    pub(crate) fn start(&self) -> StateID {
+        self.start
+    }
+
+    pub(crate) fn next(&self, state: StateID, byte: u8) -> StateID {
+        self.dfa.next_state(state, byte)
+    }
+
+    pub(crate) fn accepts(&self, state: StateID) -> bool {
+        self.dfa.is_match_state(self.dfa.next_eoi_state(state)) != self.negated
+    }
+
+    // Whether some continuation (possibly empty) from `state` is accepted, or `None` if
+    // that couldn't be decided within `size_limit` states.
+    pub(crate) fn can_accept_from(&self, state: StateID, size_limit: usize) -> Option<bool> {
+        let mut seen = HashSet::from([state]);
+        let mut queue = VecDeque::from([state]);
+        while let Some(state) = queue.pop_front() {
+            if self.accepts(state) {
+                return Some(true);
+            }
+            for byte in 0..=u8::MAX {
+                let next = self.next(state, byte);
+                if self.is_dead(next) || !seen.insert(next) {
+                    continue;
+                }
+                if seen.len() >= size_limit {
+                    return None;
+                }
+                queue.push_back(next);
+            }
+        }
+        Some(false)
+    }
+
+    // A state from which no continuation can ever be accepted.
+    pub(crate) fn is_dead(&self, state: StateID) -> bool {
+        !self.negated && self.dfa.is_dead_state(state)
+    }
+}
+
+#[cfg(feature = "automata")]
+type StatePair = (StateID, StateID);
+
+#[cfg(feature = "automata")]
+/// Searches for a haystack accepted by `a` and by `b`. Returns `None` if the search grew past
+/// `size_limit` state pairs, otherwise the example (if any).
+pub(crate) fn find_common(
+    a: &PatternDfa,
+    b: &PatternDfa,
+    size_limit: usize,
+) -> Option<Option<Vec<u8>>> {
+    let start = (a.start(), b.start());
+    let mut parents: HashMap<StatePair, Option<(StatePair, u8)>> = HashMap::from([(start, None)]);
+    let mut queue = VecDeque::from([start]);
+
+    while let Some(pair) = queue.pop_front() {
+        if a.accepts(pair.0) && b.accepts(pair.1) {
+            let mut bytes = vec![];
+            let mut current = pair;
+            while let Some(Some((parent, byte))) = parents.get(&current) {
+                bytes.push(*byte);
+                current = *parent;
+            }
+            bytes.reverse();
+            return Some(Some(bytes));
+        }
+
+        for byte in 0..=u8::MAX {
+            let next = (a.next(pair.0, byte), b.next(pair.1, byte));
+            if a.is_dead(next.0) || b.is_dead(next.1) || parents.contains_key(&next) {
+                continue;
+            }
+            if parents.len() >= size_limit {
+                return None;
+            }
+            parents.insert(next, Some((pair, byte)));
+            queue.push_back(next);
+        }
+    }
+    Some(None)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn example(a: &str, b: &str) -> Option<String> {
+        match MaybeRegex::new(a).overlaps(&MaybeRegex::new(b)) {
+            OverlapResult::Overlapping { example } => Some(example),
+            _ => None,
+        }
+    }
+
+    #[test]
+    fn literals() {
+        assert_eq!(example("foo", "bar"), Some("foobar".into()));
+        assert_eq!(example("foobar", "-bar"), None);
+        assert_eq!(example("foo", "-bar"), Some("foo".into()));
+    }
+
+    #[test]
+    #[cfg(feature = "automata")]
+    fn regexes() {
+        assert_eq!(
+            MaybeRegex::new("^foo").overlaps(&MaybeRegex::new("^bar")),
+            OverlapResult::Disjoint
+        );
+        let example = example("^foo", "[0-9]$").unwrap();
+        assert!(MaybeRegex::new("^foo").matches(&example));
+        assert!(MaybeRegex::new("[0-9]$").matches(&example));
+        assert_eq!(
+            MaybeRegex::new("^error.*").overlaps(&MaybeRegex::new("-error")),
+            OverlapResult::Disjoint
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "automata")]
+    fn subsets_and_equivalence() {
+        let broad = MaybeRegex::new("error.*");
+        assert_eq!(
+            MaybeRegex::new("error: timeout").is_subset_of(&broad),
+            Some(true)
+        );
+        assert_eq!(
+            broad.is_subset_of(&MaybeRegex::new("error: timeout")),
+            Some(false)
+        );
+        assert_eq!(
+            MaybeRegex::new("-error").is_subset_of(&MaybeRegex::new("-error: timeout")),
+            Some(true)
+        );
+        assert_eq!(
+            broad.is_equivalent_to(&MaybeRegex::new("error")),
+            Some(true)
+        );
+        assert_eq!(
+            broad.is_subset_of_with_limit(&MaybeRegex::new("[a-z]{50}"), 16),
+            None
+        );
+    }
+
+    #[test]
+    fn partial_literal_matches() {
+        assert!(MaybeRegex::new("foo").could_match("bar"));
+        assert!(MaybeRegex::new("-foo").could_match("fo"));
+        assert!(!MaybeRegex::new("-foo").could_match("xfoo"));
+    }
+
+    #[test]
+    #[cfg(feature = "automata")]
+    fn partial_matches() {
+        let id = MaybeRegex::new("^[A-Z]{3}-[0-9]+$").as_case_sensitive();
+        assert!(id.could_match(""));
+        assert!(id.could_match("AB"));
+        assert!(id.could_match("ABC-12"));
+        assert!(!id.could_match("AB1"));
+        assert!(!MaybeRegex::new("-^fo").could_match("foo"));
+    }
+}
@@ -0,0 +1,182 @@
+use crate::{
+    MaybeRegex, build_regex,
+    detect::{expand_quoted_spans, leading_case_insensitive, looks_like_regex},
+    plain_text,
+    scratch::{Folding, fold, with_prepared},
+    slash_regex,
+};
+use regex::Regex;
+use std::borrow::Cow;
+
+/// A pattern that borrows its input instead of copying it, for hot loops that build many
+/// short-lived patterns. Plain-text patterns only allocate when they have uppercase text to
+/// lowercase or escapes to remove; regexes still have to be compiled.
+///
+/// Matches exactly like the `MaybeRegex` created from the same input.
+#[derive(Debug, Clone)]
+pub struct MaybeRegexRef<'a> {
+    original: &'a str,
+    // The text searched for when there's no regex, which differs from `original` when quoted
+    // or made of escaped literals, like `foo\.bar`
+    text: Cow<'a, str>,
+    // `text` lowercased, for case-insensitive matching, when that changes it
+    folded: Option<String>,
+    regex: Option<Regex>,
+    pub is_negative: bool,
+    negated_by_suffix: bool,
+    case_sensitive: bool,
+}
+
+impl<'a> MaybeRegexRef<'a> {
+    pub fn new(s: &'a str) -> Self {
+        // Same as `split_negation`, without copying
+        let (original, is_negative, negated_by_suffix) = match s.strip_prefix('-') {
+            Some(text) if text.starts_with('-') => match text.strip_suffix('-') {
+                Some(rest) if !rest.is_empty() => (rest, true, true),
+                _ => (text, false, false),
+            },
+            Some(rest) => (rest, true, false),
+            None => match s.strip_suffix('-') {
+                Some(rest) => (rest, true, true),
+                None => (s, false, false),
+            },
+        };
+        let slash = slash_regex(original).and_then(Result::ok);
+        let case_sensitive = slash.is_some() || leading_case_insensitive(original).is_some();
+        // Detected like `MaybeRegex::new`, but borrowing quoted text
+        let expanded = expand_quoted_spans(original);
+        let plain = match &expanded {
+            Cow::Borrowed(expanded) => plain_text(expanded),
+            Cow::Owned(expanded) => plain_text(expanded).map(|text| Cow::Owned(text.into_owned())),
+        };
+        let (text, regex) = match (slash, plain) {
+            (Some(slash), _) => (Cow::Borrowed(original), Some(slash)),
+            (None, Some(text)) => (text, None),
+            // Input with `\Q...\E` spans is compiled even if it's plain text, since there's
+            // no borrowed copy of the text it stands for
+            (None, None) => match &expanded {
+                Cow::Owned(expanded) => (Cow::Borrowed(original), build_regex(expanded, true)),
+                Cow::Borrowed(_) if looks_like_regex(original) => {
+                    (Cow::Borrowed(original), build_regex(original, true))
+                }
+                Cow::Borrowed(_) => (Cow::Borrowed(original), None),
+            },
+        };
+        let folded = if regex.is_none()
+            && !case_sensitive
+            && let Cow::Owned(folded) = fold(&text)
+        {
+            Some(folded)
+        } else {
+            None
+        };
+        Self {
+            original,
+            text,
+            folded,
+            regex,
+            is_negative,
+            negated_by_suffix,
+            case_sensitive,
+        }
+    }
+
+    pub fn as_case_sensitive(mut self) -> Self {
+        if !self.case_sensitive
+            && let Some(regex) = &self.regex
+        {
+            self.regex = build_regex(regex.as_str(), false).or(self.regex);
+        }
+        self.case_sensitive = true;
+        self.folded = None;
+        self
+    }
+
+    pub fn is_regex(&self) -> bool {
+        self.regex.is_some()
+    }
+
+    pub fn to_str(&self) -> &'a str {
+        self.original
+    }
+
+    pub fn is_contained_within<S: AsRef<str>>(&self, haystack: S) -> bool {
+        with_prepared(
+            haystack.as_ref(),
+            // Regexes are compiled case-insensitive instead
+            if self.case_sensitive || self.regex.is_some() {
+                Folding::None
+            } else {
+                Folding::Unicode
+            },
+            false,
+            |haystack| match &self.regex {
+                Some(regex) => regex.is_match(haystack),
+                None => haystack.contains(self.folded.as_deref().unwrap_or(&self.text)),
+            },
+        )
+    }
+
+    pub fn matches<S: AsRef<str>>(&self, haystack: S) -> bool {
+        self.is_contained_within(haystack) != self.is_negative
+    }
+
+    pub fn into_owned(self) -> MaybeRegex {
+        let (original, is_negative) = (self.original, self.is_negative);
+        let mut pattern = MaybeRegex::new(match original.strip_prefix('-') {
+            // Escaped, so negated with the trailing marker
+            Some(_) if is_negative => format!("-{original}-"),
+            Some(_) => format!("-{original}"),
+            None if is_negative && self.negated_by_suffix => format!("{original}-"),
+            None if is_negative => format!("-{original}"),
+            None => original.to_string(),
+        });
+        // `is_negative` may have been changed since
+        pattern.is_negative = is_negative;
+        if self.case_sensitive {
+            pattern = pattern.as_case_sensitive();
+        }
+        pattern
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn matches_like_owned() {
+        let haystacks = ["Error: disk full", "warning", "error-42", "", "foo.bar"];
+        for input in [
+            "error",
+            "-error",
+            "disk-",
+            "^err.*[0-9]$",
+            "[bad",
+            "=err.r",
+            "/Err/",
+            "--v",
+            "--r-",
+            "Disk",
+            "-ERROR",
+            "foo\\.bar",
+            "-foo\\.bar",
+        ] {
+            let borrowed = MaybeRegexRef::new(input);
+            let owned = MaybeRegex::new(input);
+            assert_eq!(borrowed.is_regex(), owned.is_regex());
+            assert_eq!(borrowed.clone().into_owned(), owned);
+            for haystack in haystacks {
+                assert_eq!(borrowed.matches(haystack), owned.matches(haystack));
+            }
+        }
+        // Compiled even though the owned pattern is plain text
+        let quoted = MaybeRegexRef::new("\\Qerror-\\E");
+        for haystack in haystacks {
+            let owned = MaybeRegex::new("\\Qerror-\\E");
+            assert_eq!(quoted.matches(haystack), owned.matches(haystack));
+        }
+        let sensitive = MaybeRegexRef::new("Error").as_case_sensitive();
+        assert!(sensitive.matches("Error") && !sensitive.matches("error"));
+    }
+}
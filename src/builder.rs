@@ -0,0 +1,804 @@
+use crate::{
+    MaybeRegex, MaybeRegexError, TagWrapperData,
+    detect::{
+        expand_quoted_spans, has_uppercase, leading_case_insensitive, looks_like_regex,
+        quoted_literal, slash_delimited,
+    },
+    plain_text,
+};
+use log::error;
+use regex::RegexBuilder;
+use std::{borrow::Cow, sync::Arc};
+
+/// How a `MaybeRegexBuilder` decides whether its input is a regex.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Detection {
+    /// Guess from the input, like `MaybeRegex::new`, including `/pattern/flags` and
+    /// searching regexes made only of literals, like `foo\.bar`, as plain text.
+    #[default]
+    Auto,
+    /// Always a plain substring.
+    Literal,
+    /// Always a regex.
+    Regex,
+}
+
+/// An application's own rule for whether input is meant as a regex, used by
+/// `MaybeRegexBuilder::detector`.
+///
+/// Closures taking the input (without its negation marker) and returning `Option<bool>` work
+/// too.
+pub trait RegexDetector: Send + Sync {
+    /// `Some(true)` for a regex, `Some(false)` for plain text, or `None` to use the built-in
+    /// detection.
+    fn is_regex(&self, pattern: &str) -> Option<bool>;
+}
+
+impl<F: Fn(&str) -> Option<bool> + Send + Sync> RegexDetector for F {
+    fn is_regex(&self, pattern: &str) -> Option<bool> {
+        self(pattern)
+    }
+}
+
+/// What to do with input that looks like a regex but doesn't compile.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum FallbackPolicy {
+    /// Report it, e.g. so a user can fix their filter.
+    Error,
+    /// Match it as plain text, like `MaybeRegex::new`.
+    #[default]
+    TreatAsLiteral,
+    /// Leave the pattern out, e.g. ignore a bad line in a config file.
+    Skip,
+}
+
+/// The markers that make a `MaybeRegexBuilder`'s input negative, e.g. `!` for domains where
+/// text legitimately starts with `-`. Defaults to a leading or trailing `-`, like
+/// `MaybeRegex::new`.
+///
+/// Patterns built this way still display with `-`, the marker `MaybeRegex::new` understands.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct NegationMarkers {
+    prefix: Option<String>,
+    suffix: Option<String>,
+}
+
+impl Default for NegationMarkers {
+    fn default() -> Self {
+        Self::none().with_prefix("-").with_suffix("-")
+    }
+}
+
+impl NegationMarkers {
+    /// Nothing makes input negative.
+    pub fn none() -> Self {
+        Self {
+            prefix: None,
+            suffix: None,
+        }
+    }
+
+    /// Only a leading `prefix` makes input negative.
+    pub fn prefix<S: Into<String>>(prefix: S) -> Self {
+        Self::none().with_prefix(prefix)
+    }
+
+    /// Only a trailing `suffix` makes input negative.
+    pub fn suffix<S: Into<String>>(suffix: S) -> Self {
+        Self::none().with_suffix(suffix)
+    }
+
+    /// An empty prefix turns prefix negation off.
+    pub fn with_prefix<S: Into<String>>(mut self, prefix: S) -> Self {
+        self.prefix = Some(prefix.into()).filter(|prefix| !prefix.is_empty());
+        self
+    }
+
+    /// An empty suffix turns suffix negation off.
+    pub fn with_suffix<S: Into<String>>(mut self, suffix: S) -> Self {
+        self.suffix = Some(suffix.into()).filter(|suffix| !suffix.is_empty());
+        self
+    }
+
+    // Like `split_negation`, with these markers. The prefix wins when both are present, and a
+    // doubled prefix stands for itself in the text.
+    pub(crate) fn split(&self, s: &str) -> (String, bool, bool) {
+        let strip_suffix = |s: &str| {
+            let suffix = self.suffix.as_deref()?;
+            s.strip_suffix(suffix).map(str::to_string)
+        };
+        match self.prefix.as_deref() {
+            Some(prefix) if s.starts_with(&prefix.repeat(2)) => {
+                let text = &s[prefix.len()..];
+                match strip_suffix(text).filter(|rest| rest.len() >= prefix.len()) {
+                    Some(rest) => (rest, true, true),
+                    None => (text.to_string(), false, false),
+                }
+            }
+            Some(prefix) if s.starts_with(prefix) => (s[prefix.len()..].to_string(), true, false),
+            _ => match strip_suffix(s) {
+                Some(rest) => (rest, true, true),
+                None => (s.to_string(), false, false),
+            },
+        }
+    }
+}
+
+/// Creates a `MaybeRegex` with options that can't be changed after construction.
+///
+/// ```
+/// use maybe_regex::{Detection, MaybeRegex};
+///
+/// let pattern = MaybeRegex::builder("^- item$")
+///     .multi_line(true)
+///     .negation(false)
+///     .detection(Detection::Regex)
+///     .try_build()
+///     .unwrap();
+/// assert!(!pattern.is_negative);
+/// assert!(pattern.matches("list:\n- item\n"));
+/// ```
+#[derive(Clone)]
+pub struct MaybeRegexBuilder {
+    pattern: String,
+    case_sensitive: bool,
+    smart_case: bool,
+    multi_line: bool,
+    crlf: bool,
+    dot_matches_new_line: bool,
+    unicode: bool,
+    ascii: bool,
+    whole_word: bool,
+    word_characters: String,
+    negation: NegationMarkers,
+    detection: Detection,
+    allow_empty: bool,
+    max_len: Option<usize>,
+    fallback: FallbackPolicy,
+    detector: Option<Arc<dyn RegexDetector>>,
+}
+
+impl std::fmt::Debug for MaybeRegexBuilder {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("MaybeRegexBuilder")
+            .field("pattern", &self.pattern)
+            .field("case_sensitive", &self.case_sensitive)
+            .field("smart_case", &self.smart_case)
+            .field("multi_line", &self.multi_line)
+            .field("crlf", &self.crlf)
+            .field("dot_matches_new_line", &self.dot_matches_new_line)
+            .field("unicode", &self.unicode)
+            .field("ascii", &self.ascii)
+            .field("whole_word", &self.whole_word)
+            .field("word_characters", &self.word_characters)
+            .field("negation", &self.negation)
+            .field("detection", &self.detection)
+            .field("allow_empty", &self.allow_empty)
+            .field("max_len", &self.max_len)
+            .field("fallback", &self.fallback)
+            .field("detector", &self.detector.is_some())
+            .finish()
+    }
+}
+
+impl MaybeRegex {
+    pub fn builder<S: Into<String>>(pattern: S) -> MaybeRegexBuilder {
+        MaybeRegexBuilder::new(pattern)
+    }
+}
+
+impl MaybeRegexBuilder {
+    pub fn new<S: Into<String>>(pattern: S) -> Self {
+        Self {
+            pattern: pattern.into(),
+            case_sensitive: false,
+            smart_case: false,
+            multi_line: false,
+            crlf: false,
+            dot_matches_new_line: false,
+            unicode: true,
+            ascii: false,
+            whole_word: false,
+            word_characters: String::new(),
+            negation: NegationMarkers::default(),
+            detection: Detection::Auto,
+            allow_empty: true,
+            max_len: None,
+            fallback: FallbackPolicy::TreatAsLiteral,
+            detector: None,
+        }
+    }
+
+    pub fn case_sensitive(mut self, case_sensitive: bool) -> Self {
+        self.case_sensitive = case_sensitive;
+        self
+    }
+
+    /// Makes the pattern case sensitive if it has an uppercase letter and case insensitive
+    /// otherwise, like ripgrep's `--smart-case`. Escapes like `\S` don't count. Overrides
+    /// `case_sensitive` when on.
+    pub fn smart_case(mut self, smart_case: bool) -> Self {
+        self.smart_case = smart_case;
+        self
+    }
+
+    /// Makes `^` and `$` match at line boundaries.
+    pub fn multi_line(mut self, multi_line: bool) -> Self {
+        self.multi_line = multi_line;
+        self
+    }
+
+    /// Lets `^` and `$` treat `\r\n` as a line ending too when `multi_line` is on, so patterns
+    /// written against Unix logs work on files from Windows.
+    pub fn crlf(mut self, crlf: bool) -> Self {
+        self.crlf = crlf;
+        self
+    }
+
+    /// Lets `.` match `\n`.
+    pub fn dot_matches_new_line(mut self, dot_matches_new_line: bool) -> Self {
+        self.dot_matches_new_line = dot_matches_new_line;
+        self
+    }
+
+    /// Whether classes like `\w` and `.` are Unicode-aware. On by default. Regexes that could
+    /// match part of a character without it, like `a.c` or `[^a]`, stay Unicode, since
+    /// haystacks are still UTF-8.
+    pub fn unicode(mut self, unicode: bool) -> Self {
+        self.unicode = unicode;
+        self
+    }
+
+    /// Only folds the case of ASCII letters, and makes regex classes like `\w` ASCII-only, for
+    /// haystacks known to be ASCII, like machine-generated logs. This is cheaper to compile and
+    /// match. Regexes that could match part of a character, like `.` or `[^a]`, stay Unicode,
+    /// since haystacks are still UTF-8.
+    pub fn ascii(mut self, ascii: bool) -> Self {
+        self.ascii = ascii;
+        self
+    }
+
+    /// Only matches with a word boundary on either side, so `cat` doesn't match `concatenate`,
+    /// whether the input turns out to be a regex or plain text.
+    pub fn whole_word(mut self, whole_word: bool) -> Self {
+        self.whole_word = whole_word;
+        self
+    }
+
+    /// Characters that count as part of a word for `whole_word`, besides letters, digits and
+    /// `_`, e.g. `-.` for tag names like `rust-lang.org` or `:` for log keys. A `\b` written in
+    /// a regex keeps its usual meaning.
+    pub fn word_characters<S: Into<String>>(mut self, characters: S) -> Self {
+        self.word_characters = characters.into();
+        self
+    }
+
+    /// Whether a leading or trailing `-` makes the pattern negative. On by default; when off,
+    /// the `-` is part of the pattern.
+    pub fn negation(mut self, negation: bool) -> Self {
+        self.negation = if negation {
+            NegationMarkers::default()
+        } else {
+            NegationMarkers::none()
+        };
+        self
+    }
+
+    /// Whether a trailing `-` makes the pattern negative, e.g. off so `2021-` or `foo-` are
+    /// searched for as written. A leading `-` still does unless `negation` is off.
+    pub fn trailing_negation(mut self, trailing_negation: bool) -> Self {
+        let suffix = if trailing_negation { "-" } else { "" };
+        self.negation = self.negation.with_suffix(suffix);
+        self
+    }
+
+    /// Which markers make the pattern negative, replacing the leading or trailing `-`.
+    pub fn negation_markers(mut self, markers: NegationMarkers) -> Self {
+        self.negation = markers;
+        self
+    }
+
+    pub fn detection(mut self, detection: Detection) -> Self {
+        self.detection = detection;
+        self
+    }
+
+    /// Consulted before the built-in detection when `detection` is `Detection::Auto`.
+    pub fn detector<D: RegexDetector + 'static>(mut self, detector: D) -> Self {
+        self.detector = Some(Arc::new(detector));
+        self
+    }
+
+    /// Whether a pattern with nothing left after removing the negation marker, which matches
+    /// everything (or, negated, nothing), is allowed. On by default.
+    pub fn allow_empty(mut self, allow_empty: bool) -> Self {
+        self.allow_empty = allow_empty;
+        self
+    }
+
+    /// Rejects input longer than `max_len` bytes, e.g. to bound the cost of user-supplied
+    /// patterns.
+    pub fn max_len(mut self, max_len: usize) -> Self {
+        self.max_len = Some(max_len);
+        self
+    }
+
+    /// Used by `build_with_fallback`.
+    pub fn fallback(mut self, fallback: FallbackPolicy) -> Self {
+        self.fallback = fallback;
+        self
+    }
+
+    /// Like `try_build`, but handles a regex that doesn't compile according to the fallback
+    /// policy, returning `None` if it says to skip the pattern.
+    pub fn build_with_fallback(&self) -> Result<Option<MaybeRegex>, MaybeRegexError> {
+        match (self.try_build(), self.fallback) {
+            (Err(MaybeRegexError::InvalidRegex { .. }), FallbackPolicy::TreatAsLiteral) => self
+                .clone()
+                .detection(Detection::Literal)
+                .try_build()
+                .map(Some),
+            (Err(MaybeRegexError::InvalidRegex { .. }), FallbackPolicy::Skip) => Ok(None),
+            (result, _) => result.map(Some),
+        }
+    }
+
+    /// Like `try_build`, but a regex that doesn't compile is logged and matched as plain text,
+    /// as with `MaybeRegex::new`. Other errors give `MaybeRegex::match_none()`.
+    pub fn build(&self) -> MaybeRegex {
+        match self.try_build() {
+            Ok(pattern) => pattern,
+            Err(e @ MaybeRegexError::InvalidRegex { .. }) => {
+                error!("{e}");
+                self.clone().detection(Detection::Literal).build()
+            }
+            Err(e) => {
+                error!("{e}");
+                MaybeRegex::match_none()
+            }
+        }
+    }
+
+    pub fn try_build(&self) -> Result<MaybeRegex, MaybeRegexError> {
+        if let Some(max_len) = self.max_len
+            && self.pattern.len() > max_len
+        {
+            return Err(MaybeRegexError::PatternTooLong {
+                len: self.pattern.len(),
+                max_len,
+            });
+        }
+        let (s, is_negative, negated_by_suffix) = self.negation.split(&self.pattern);
+        if s.is_empty() && !self.allow_empty {
+            return Err(MaybeRegexError::EmptyPattern);
+        }
+        // `/pattern/flags` is always a regex, case sensitive without `i`, like in `MaybeRegex::new`
+        let slash = match self.detection {
+            Detection::Auto => slash_delimited(&s),
+            _ => None,
+        };
+        let slash_flags = slash.map_or("", |(_, flags)| flags);
+        let source = match (self.detection, slash) {
+            (_, Some((pattern, _))) => Cow::Borrowed(pattern),
+            (Detection::Literal, _) => Cow::Borrowed(s.as_str()),
+            _ => expand_quoted_spans(&s),
+        };
+        let detected = match self.detection {
+            Detection::Auto if slash.is_none() => self
+                .detector
+                .as_ref()
+                .and_then(|detector| detector.is_regex(&source)),
+            _ => None,
+        };
+        // Quoted input is always plain text, and so are regexes made only of literals, like
+        // `foo\.bar`, unless the detector says otherwise
+        let plain = match (self.detection, detected) {
+            (Detection::Auto, _) if slash.is_some() => None,
+            (Detection::Auto, None) => plain_text(&source),
+            (Detection::Auto, Some(_)) => quoted_literal(&source).map(Cow::Borrowed),
+            _ => None,
+        };
+        let is_regex = slash.is_some()
+            || (plain.is_none()
+                && match self.detection {
+                    Detection::Auto => detected.unwrap_or_else(|| looks_like_regex(&source)),
+                    Detection::Literal => false,
+                    Detection::Regex => true,
+                });
+
+        let text = plain.as_deref().unwrap_or(&source);
+        // Slash input's case is settled by its `i` flag
+        let case_sensitive = if slash.is_some() {
+            true
+        } else if self.smart_case {
+            has_uppercase(text, is_regex)
+        } else {
+            self.case_sensitive
+        };
+        // Looked for before any `\b`s are added
+        let inline_case = is_regex && leading_case_insensitive(&source).is_some();
+        // Custom word characters are checked around each hit instead
+        let wrap_in_boundaries = self.whole_word && is_regex && self.word_characters.is_empty();
+        let data = if is_regex {
+            let source = if wrap_in_boundaries {
+                format!(r"\b(?:{source})\b")
+            } else {
+                source.to_string()
+            };
+            // Options are written into the source as inline flags, since sets and
+            // `as_case_sensitive` rebuild from it
+            let build = |unicode: bool| {
+                let slash_flag = |flag| slash_flags.contains(flag);
+                let mut flags: String = [
+                    (self.multi_line || slash_flag('m'), 'm'),
+                    (self.dot_matches_new_line || slash_flag('s'), 's'),
+                    (self.crlf, 'R'),
+                    (slash_flag('i'), 'i'),
+                    (slash_flag('x'), 'x'),
+                    (slash_flag('U'), 'U'),
+                ]
+                .into_iter()
+                .filter_map(|(on, flag)| on.then_some(flag))
+                .collect();
+                if !unicode {
+                    flags.push_str("-u");
+                }
+                let source = if flags.is_empty() {
+                    Cow::Borrowed(source.as_str())
+                } else {
+                    Cow::Owned(format!("(?{flags}){source}"))
+                };
+                RegexBuilder::new(&source)
+                    .case_insensitive(!case_sensitive)
+                    .build()
+            };
+            // Without Unicode, regexes that could match part of a character, like `a.c`,
+            // don't compile against UTF-8 haystacks, so they keep it
+            let unicode = self.unicode && !self.ascii;
+            let regex = match build(unicode) {
+                Err(_) if !unicode => build(true),
+                result => result,
+            }
+            .map_err(|error| MaybeRegexError::InvalidRegex {
+                pattern: s.clone(),
+                error,
+            })?;
+            TagWrapperData::Regex(regex)
+        } else {
+            TagWrapperData::Raw(text.to_string())
+        };
+        let mut pattern = MaybeRegex::with_data(data, s, is_negative, negated_by_suffix);
+        pattern.ascii = self.ascii;
+        pattern.whole_word = self.whole_word && !wrap_in_boundaries;
+        pattern.word_characters = self.word_characters.clone();
+        pattern.refold();
+        // The regex was already compiled with the right case sensitivity
+        Ok(if case_sensitive || inline_case {
+            pattern.with_regex_case()
+        } else {
+            pattern
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::MaybeRegexSet;
+
+    #[test]
+    fn reports_structured_errors() {
+        assert_eq!(
+            MaybeRegex::builder("-").allow_empty(false).try_build(),
+            Err(MaybeRegexError::EmptyPattern)
+        );
+        assert_eq!(
+            MaybeRegex::builder("abcdef").max_len(4).try_build(),
+            Err(MaybeRegexError::PatternTooLong { len: 6, max_len: 4 })
+        );
+        assert!(
+            !MaybeRegex::builder("abcdef")
+                .max_len(4)
+                .build()
+                .matches("abcdef")
+        );
+
+        let error = MaybeRegex::builder("a{99999}{99999}")
+            .detection(Detection::Regex)
+            .try_build()
+            .unwrap_err();
+        assert!(std::error::Error::source(&error).is_some());
+        assert!(error.to_string().starts_with("Invalid regex"));
+    }
+
+    #[test]
+    fn consults_custom_detector() {
+        // Only slash-delimited input is a regex, e.g. `/a.c/`; no opinion otherwise
+        let slashes =
+            |s: &str| (s.len() > 1 && s.starts_with('/') && s.ends_with('/')).then_some(true);
+        assert!(
+            MaybeRegex::builder("/a.c/")
+                .detector(slashes)
+                .build()
+                .is_regex()
+        );
+        assert!(
+            MaybeRegex::builder("a.c")
+                .detector(slashes)
+                .build()
+                .is_regex()
+        );
+
+        struct NeverRegex;
+        impl RegexDetector for NeverRegex {
+            fn is_regex(&self, _: &str) -> Option<bool> {
+                Some(false)
+            }
+        }
+        let literal = MaybeRegex::builder("a.c").detector(NeverRegex).build();
+        assert!(!literal.is_regex() && !literal.matches("abc"));
+        let forced = MaybeRegex::builder("a.c")
+            .detector(NeverRegex)
+            .detection(Detection::Regex)
+            .build();
+        assert!(forced.is_regex());
+    }
+
+    #[test]
+    fn uses_configured_negation_markers() {
+        let bang = NegationMarkers::prefix("!");
+        let tag = MaybeRegex::builder("-v")
+            .negation_markers(bang.clone())
+            .build();
+        assert!(!tag.is_negative && tag.matches("ls -v"));
+        let excluded = MaybeRegex::builder("!-v").negation_markers(bang).build();
+        assert!(excluded.is_negative && !excluded.matches("ls -v"));
+        assert_eq!(excluded.to_string(), "--v-");
+        let escaped = MaybeRegex::builder("!!x")
+            .negation_markers(NegationMarkers::prefix("!"))
+            .build();
+        assert!(!escaped.is_negative && escaped.matches("a !x"));
+
+        let suffix = NegationMarkers::suffix(" NOT");
+        let pattern = MaybeRegex::builder("draft NOT")
+            .negation_markers(suffix)
+            .build();
+        assert!(pattern.is_negative && pattern.matches("final"));
+
+        let none = MaybeRegex::builder("-x-")
+            .negation_markers(NegationMarkers::default().with_prefix(""))
+            .build();
+        assert_eq!((none.to_str(), none.is_negative), ("-x", true));
+    }
+
+    #[test]
+    fn disables_trailing_negation() {
+        let year = MaybeRegex::builder("2021-")
+            .trailing_negation(false)
+            .build();
+        assert!(!year.is_negative && year.matches("2021-05") && !year.matches("2021"));
+        let excluded = MaybeRegex::builder("-2021-")
+            .trailing_negation(false)
+            .build();
+        assert!(excluded.is_negative && excluded.to_str() == "2021-");
+        assert!(
+            MaybeRegex::builder("x-")
+                .trailing_negation(false)
+                .trailing_negation(true)
+                .build()
+                .is_negative
+        );
+    }
+
+    #[test]
+    fn applies_smart_case() {
+        let smart = |s: &str| MaybeRegex::builder(s).smart_case(true).build();
+        assert!(smart("error").matches("ERROR"));
+        assert!(!smart("Error").matches("ERROR") && smart("Error").matches("Error"));
+        assert!(smart("\\Serror").matches("xERROR"));
+        assert!(!smart("-Debug").matches("Debug") && smart("-Debug").matches("debug"));
+        assert!(
+            MaybeRegex::builder("error")
+                .case_sensitive(true)
+                .smart_case(true)
+                .build()
+                .matches("ERROR")
+        );
+    }
+
+    #[test]
+    fn detects_like_new_by_default() {
+        let haystacks = ["Error: disk", "error", "foo.bar", "fooxbar", "a\nERR"];
+        for input in [
+            "/Err/",
+            "/err/i",
+            "/^err/mi",
+            "foo\\.bar",
+            "=foo.bar",
+            "^err.*",
+        ] {
+            let built = MaybeRegex::builder(input).build();
+            let new = MaybeRegex::new(input);
+            assert_eq!(built.is_regex(), new.is_regex(), "{input}");
+            for haystack in haystacks {
+                assert_eq!(built.matches(haystack), new.matches(haystack), "{input}");
+            }
+        }
+        let forced = MaybeRegex::builder("foo\\.bar").detector(|_: &str| Some(true));
+        assert!(forced.build().is_regex());
+    }
+
+    #[test]
+    fn matches_whole_words() {
+        let word = |s: &str| MaybeRegex::builder(s).whole_word(true).build();
+        assert!(word("cat").matches("a Cat sat") && !word("cat").matches("concatenate"));
+        assert!(word("c.t").is_regex() && !word("c.t").matches("concatenate"));
+        assert!(word("c.t").matches("the cot"));
+        assert!(word("=1+1").matches("is 1+1 2") && !word("=1+1").matches("11+12"));
+        assert!(!word("-cat").matches("cat") && word("-cat").matches("concatenate"));
+
+        let multi_line = MaybeRegex::builder("^error$")
+            .whole_word(true)
+            .multi_line(true)
+            .build();
+        assert!(multi_line.matches("ok\nerror\nok"));
+        let inline = MaybeRegex::builder("(?-i)Cat").whole_word(true).build();
+        assert!(inline.matches("Cat") && !inline.matches("cat"));
+    }
+
+    #[test]
+    fn uses_custom_word_characters() {
+        let tag = |s: &str| {
+            MaybeRegex::builder(s)
+                .whole_word(true)
+                .word_characters("-.")
+                .build()
+        };
+        assert!(tag("rust").matches("tags: rust, go") && !tag("rust").matches("rust-lang"));
+        let plain = MaybeRegex::builder("rust").whole_word(true).build();
+        assert!(plain.matches("rust-lang"));
+
+        let regex = tag("ru.t");
+        assert!(regex.is_regex() && regex.matches("RUST") && !regex.matches("rust.org"));
+        assert_eq!(regex.match_indices("rust.org rust"), [(9, 4)]);
+        assert!(regex.matches_exactly("rust") && regex.ends_with("a rust"));
+
+        let set = MaybeRegexSet::builder()
+            .whole_word(true)
+            .word_characters(":")
+            .pattern("level")
+            .build()
+            .unwrap();
+        assert!(set.matches("level = 3") && !set.matches("level:warn"));
+    }
+
+    #[test]
+    fn anchors_lines_ending_in_crlf() {
+        let line = |crlf: bool| {
+            MaybeRegex::builder("^error$")
+                .multi_line(true)
+                .crlf(crlf)
+                .build()
+        };
+        assert!(line(true).matches("ok\r\nerror\r\nok") && line(true).matches("ok\nerror"));
+        assert!(!line(false).matches("ok\r\nerror\r\nok"));
+    }
+
+    #[test]
+    fn keeps_crlf_when_rebuilt() {
+        let haystack = "ok\r\nerror\r\nok";
+        let line = MaybeRegex::builder("^error$")
+            .multi_line(true)
+            .crlf(true)
+            .build();
+        assert!(line.clone().as_case_sensitive().matches(haystack));
+        assert!(line.clone().as_whole_word().matches(haystack));
+        let set: MaybeRegexSet = [line].into_iter().collect();
+        assert!(set.compile_report().compiled && set.matches(haystack));
+    }
+
+    #[test]
+    fn folds_only_ascii_in_ascii_mode() {
+        let ascii = |s: &str| MaybeRegex::builder(s).ascii(true).build();
+        assert!(ascii("Error").matches("ERROR") && ascii("error").matches("eRrOr"));
+        assert!(!ascii("école").matches("ÉCOLE") && ascii("école").matches("éCOLE"));
+        assert!(!ascii("straße").matches("STRASSE"));
+        assert!(ascii("^\\w+$").matches("Abc") && !ascii("^\\w+$").matches("abé"));
+        assert!(ascii("a.c").is_regex() && ascii("a.c").matches("AbC"));
+    }
+
+    #[test]
+    fn keeps_unicode_off_where_it_compiles() {
+        let dot = MaybeRegex::builder("a.c").unicode(false).build();
+        assert!(dot.is_regex() && dot.matches("abc") && dot.matches("aéc"));
+
+        let word = MaybeRegex::builder("^\\w+$").unicode(false).build();
+        assert!(word.matches("abc") && !word.matches("abé"));
+        assert!(!word.clone().as_case_sensitive().matches("abé"));
+        let set: MaybeRegexSet = [word].into_iter().collect();
+        assert!(set.compile_report().compiled);
+        assert!(set.matches("abc") && !set.matches("abé"));
+    }
+
+    #[test]
+    fn applies_fallback_policy() {
+        let builder = MaybeRegex::builder("[a-z");
+        assert!(
+            builder
+                .clone()
+                .fallback(FallbackPolicy::Error)
+                .build_with_fallback()
+                .is_err()
+        );
+        assert_eq!(
+            builder
+                .clone()
+                .fallback(FallbackPolicy::Skip)
+                .build_with_fallback(),
+            Ok(None)
+        );
+        let literal = builder.build_with_fallback().unwrap().unwrap();
+        assert!(literal.matches("x[a-z"));
+
+        let valid = MaybeRegex::builder("[a-c]").fallback(FallbackPolicy::Skip);
+        assert!(valid.build_with_fallback().unwrap().is_some());
+    }
+
+    #[test]
+    fn keeps_line_options_when_rebuilt() {
+        let multi_line = MaybeRegex::builder("^error$").multi_line(true).build();
+        let dot_all = MaybeRegex::builder("a.b")
+            .dot_matches_new_line(true)
+            .build();
+        for (pattern, haystack) in [(multi_line, "ok\nerror\nok"), (dot_all, "a\nb")] {
+            assert!(pattern.matches(haystack));
+            assert!(pattern.clone().as_case_sensitive().matches(haystack));
+            let set: MaybeRegexSet = [pattern].into_iter().collect();
+            assert!(set.compile_report().compiled && set.matches(haystack));
+        }
+    }
+
+    #[test]
+    fn applies_construction_options() {
+        let pattern = MaybeRegex::builder("^b.c$").build();
+        assert!(!pattern.matches("a\nb\nc"));
+        let pattern = MaybeRegex::builder("^b.c$")
+            .multi_line(true)
+            .dot_matches_new_line(true)
+            .build();
+        assert!(pattern.matches("a\nb\nc\nd"));
+
+        let pattern = MaybeRegex::builder("Error [A-Z]+")
+            .case_sensitive(true)
+            .build();
+        assert!(pattern.matches("Error ABC"));
+        assert!(!pattern.matches("error ABC"));
+        assert!(!pattern.matches("Error abc"));
+
+        let pattern = MaybeRegex::builder("-1.5")
+            .negation(false)
+            .detection(Detection::Literal)
+            .build();
+        assert!(pattern.matches("x = -1.5"));
+        assert!(!pattern.matches("x = -105"));
+
+        assert!(
+            MaybeRegex::builder("café|tea")
+                .detection(Detection::Regex)
+                .build()
+                .matches("CAFÉ")
+        );
+        assert!(
+            MaybeRegex::builder("(")
+                .detection(Detection::Regex)
+                .try_build()
+                .is_err()
+        );
+        assert!(
+            MaybeRegex::builder("(")
+                .detection(Detection::Regex)
+                .build()
+                .matches("f(x)")
+        );
+    }
+}
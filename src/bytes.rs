@@ -0,0 +1,121 @@
+use crate::{
+    MaybeRegex, TagWrapperData,
+    scratch::{Folding, fold, fold_ascii},
+};
+use memchr::memmem;
+use std::borrow::Cow;
+
+impl MaybeRegex {
+    /// Like `is_contained_within`, but for raw bytes, so records don't need to be validated
+    /// as UTF-8 first. Plain-text needles are found with a byte search, after folding the case
+    /// of the valid UTF-8 in the haystack like `is_contained_within` when case-insensitive;
+    /// everything else runs as a `regex::bytes` regex, built once and cached on the pattern.
+    pub fn is_contained_within_bytes<B: AsRef<[u8]>>(&self, haystack: B) -> bool {
+        let haystack = haystack.as_ref();
+        if haystack.len() < self.min_len {
+            return false;
+        }
+        let haystack = if self.normalize_line_endings && memmem::find(haystack, b"\r\n").is_some() {
+            Cow::Owned(crlf_to_lf(haystack))
+        } else {
+            Cow::Borrowed(haystack)
+        };
+
+        if let TagWrapperData::Raw(value) = &self.data
+            && !self.whole_word
+        {
+            let needle = self.needle(value).as_bytes();
+            return match self.folding() {
+                Folding::None => memmem::find(&haystack, needle).is_some(),
+                folding => memmem::find(&fold_bytes(&haystack, folding), needle).is_some(),
+            };
+        }
+        let pattern = self.regex_source().into_owned();
+        let source = if self.case_sensitive {
+            pattern
+        } else {
+            format!("(?i:{pattern})")
+        };
+        self.variants
+            .get_bytes(source)
+            .is_some_and(|regex| regex.is_match(&haystack))
+    }
+
+    /// Like `matches`, but for raw bytes. See `is_contained_within_bytes`.
+    pub fn matches_bytes<B: AsRef<[u8]>>(&self, haystack: B) -> bool {
+        self.is_contained_within_bytes(haystack) != self.is_negative
+    }
+}
+
+// Folds the valid UTF-8 in `haystack` the way `&str` haystacks are folded, keeping invalid
+// bytes as they are.
+fn fold_bytes(haystack: &[u8], folding: Folding) -> Vec<u8> {
+    let mut output = Vec::with_capacity(haystack.len());
+    for chunk in haystack.utf8_chunks() {
+        let folded = match folding {
+            Folding::Ascii => fold_ascii(chunk.valid()),
+            _ => fold(chunk.valid()),
+        };
+        output.extend_from_slice(folded.as_bytes());
+        output.extend_from_slice(chunk.invalid());
+    }
+    output
+}
+
+fn crlf_to_lf(haystack: &[u8]) -> Vec<u8> {
+    let mut output = Vec::with_capacity(haystack.len());
+    let mut last = 0;
+    for index in memmem::find_iter(haystack, b"\r\n") {
+        output.extend_from_slice(&haystack[last..index]);
+        last = index + 1;
+    }
+    output.extend_from_slice(&haystack[last..]);
+    output
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn matches_raw_bytes() {
+        let record: &[u8] = b"\xFF\xFEuser=Alice; status=ERROR 500";
+        assert!(MaybeRegex::new("error").is_contained_within_bytes(record));
+        assert!(
+            MaybeRegex::new("ERROR")
+                .as_case_sensitive()
+                .is_contained_within_bytes(record)
+        );
+        assert!(
+            !MaybeRegex::new("error")
+                .as_case_sensitive()
+                .is_contained_within_bytes(record)
+        );
+        assert!(MaybeRegex::new("status=[a-z]+ [0-9]{3}").matches_bytes(record));
+        assert!(MaybeRegex::new("-warn").matches_bytes(record));
+        assert!(!MaybeRegex::new("-alice").matches_bytes(record));
+
+        let crlf = MaybeRegex::new("a\nb").as_line_ending_insensitive();
+        assert!(crlf.matches_bytes(b"a\r\nb"));
+        assert_eq!(crlf_to_lf(b"x\r\ny\r\r\n"), b"x\ny\r\n");
+    }
+
+    #[test]
+    fn folds_literals_like_str() {
+        for (needle, haystack) in [
+            ("straße", "STRASSE"),
+            ("STRASSE", "straße"),
+            ("école", "L'ÉCOLE"),
+        ] {
+            let pattern = MaybeRegex::new(needle);
+            assert!(pattern.is_contained_within(haystack), "{needle}");
+            let mut record = b"\xFF".to_vec();
+            record.extend_from_slice(haystack.as_bytes());
+            assert!(pattern.is_contained_within_bytes(&record), "{needle}");
+        }
+        let ascii = MaybeRegex::builder("école").ascii(true).build();
+        assert!(
+            !ascii.is_contained_within_bytes("ÉCOLE") && ascii.is_contained_within_bytes("éCOLE")
+        );
+    }
+}
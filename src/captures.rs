@@ -0,0 +1,115 @@
+use crate::{Match, MaybeRegex, TagWrapperData};
+use std::ops::Range;
+
+/// The groups of one match, from `MaybeRegex::captures` or `captures_iter`. Group 0 is the
+/// whole match. Plain text patterns have no other groups.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Captures<'h> {
+    haystack: &'h str,
+    groups: Vec<Option<Range<usize>>>,
+}
+
+impl<'h> Captures<'h> {
+    /// The match for group `i`, or `None` if there's no such group or it didn't participate
+    /// in the match, like `(b)` in `a|(b)` matching `a`.
+    pub fn get(&self, i: usize) -> Option<Match<'h>> {
+        let range = self.groups.get(i)?.clone()?;
+        Some(Match::new(self.haystack, range))
+    }
+
+    /// The number of groups, including group 0 and those that didn't participate.
+    pub fn len(&self) -> usize {
+        self.groups.len()
+    }
+
+    /// Always false, since group 0 is always there.
+    pub fn is_empty(&self) -> bool {
+        self.groups.is_empty()
+    }
+
+    /// Every group in order, starting with group 0.
+    pub fn iter(&self) -> impl Iterator<Item = Option<Match<'h>>> + '_ {
+        (0..self.len()).map(|i| self.get(i))
+    }
+}
+
+impl MaybeRegex {
+    /// The groups of the first match in the haystack, ignoring negation. Plain text patterns
+    /// only have group 0, the whole match.
+    ///
+    /// ```
+    /// use maybe_regex::MaybeRegex;
+    ///
+    /// let pattern = MaybeRegex::new("(\\w+)@(\\w+)");
+    /// let captures = pattern.captures("mail me@example.com").unwrap();
+    /// assert_eq!(captures.get(2).unwrap().as_str(), "example");
+    /// ```
+    pub fn captures<'h>(&self, haystack: &'h str) -> Option<Captures<'h>> {
+        self.captures_iter(haystack).next()
+    }
+
+    /// The groups of every match, found one at a time like `find_iter`.
+    pub fn captures_iter<'h>(&self, haystack: &'h str) -> impl Iterator<Item = Captures<'h>> {
+        self.search_iter(
+            haystack,
+            |text, from| match &self.data {
+                TagWrapperData::Regex(regex) => {
+                    let captures = regex.captures_at(text, from)?;
+                    let groups: Vec<_> = captures
+                        .iter()
+                        .map(|group| group.map(|group| group.range()))
+                        .collect();
+                    Some((groups[0].clone()?, groups))
+                }
+                TagWrapperData::Raw(_) => {
+                    let found = self.find_from(text, from)?;
+                    Some((found.clone(), vec![Some(found)]))
+                }
+            },
+            |found, groups, to_original| {
+                let mut groups: Vec<_> = groups
+                    .into_iter()
+                    .map(|group| group.map(&mut *to_original))
+                    .collect();
+                groups[0] = Some(to_original(found));
+                Some(Captures { haystack, groups })
+            },
+        )
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn captures_groups() {
+        let pattern = MaybeRegex::new("(\\d+)-(\\d+)?");
+        let all: Vec<Vec<_>> = pattern
+            .captures_iter("1-2 3-")
+            .map(|captures| {
+                captures
+                    .iter()
+                    .map(|group| group.map(|group| group.as_str()))
+                    .collect()
+            })
+            .collect();
+        assert_eq!(
+            all,
+            [
+                vec![Some("1-2"), Some("1"), Some("2")],
+                vec![Some("3-"), Some("3"), None]
+            ]
+        );
+
+        let literal = MaybeRegex::new("Disk").captures("a DISK").unwrap();
+        assert_eq!(literal.len(), 1);
+        assert_eq!(literal.get(0).map(|group| group.range()), Some(2..6));
+        assert_eq!(literal.get(1), None);
+
+        let crlf = MaybeRegex::new("(?m)^(b)$").as_line_ending_insensitive();
+        let captures = crlf.captures("a\r\nb\r\n").unwrap();
+        assert_eq!(captures.get(1).map(|group| group.range()), Some(3..4));
+        assert!(MaybeRegex::new("x(y)").captures("abc").is_none());
+    }
+}
@@ -0,0 +1,179 @@
+use crate::{
+    MaybeRegex, REGEX_REGEX, TagWrapperData, TokenKind,
+    detect::{
+        expand_quoted_spans, is_question, literal_text, quoted_literal, repeats_repetition,
+        slash_delimited, uses_regex_syntax,
+    },
+};
+use lazy_static::lazy_static;
+use regex::{Regex, RegexBuilder};
+use regex_syntax::ast::{ErrorKind, parse::Parser};
+use std::ops::Range;
+
+lazy_static! {
+    // Constructs that hardly ever show up in plain text.
+    static ref STRONG_REGEX_SIGNAL: Regex = Regex::new(
+        r"\\[dwsbDWSB]|\.[*+?]|\[[^\]]+\]|^\^|\$$|\(\?|\{[0-9]+(,[0-9]*)?\}").unwrap();
+}
+
+/// How sure the regex-or-literal heuristic is about its guess.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Confidence {
+    Definite,
+    Likely,
+    /// Reasonable people could read it either way, e.g. `example.com` or `c++`. Worth asking
+    /// the user rather than guessing.
+    Ambiguous,
+}
+
+/// Why a pattern was treated as a regex or as plain text.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DetectionReason {
+    /// It uses regex syntax and compiled.
+    RegexSyntax,
+    /// It doesn't use any regex syntax.
+    NoRegexSyntax,
+    /// It's a regex made only of escaped characters, like `example\.com`, so it was searched
+    /// for as the plain text it matches.
+    EscapedLiteral,
+    /// It reads as a question, like `is this a regex?`, despite the trailing `?`.
+    Question,
+    /// It repeats a repetition, like `C++`, which is almost certainly meant as text.
+    StackedRepetition,
+    /// It looked like a regex but didn't compile, so it was searched for as plain text.
+    CompileError(String),
+    /// It was written as `/pattern/flags`, which is always a regex.
+    Delimited,
+    /// It was quoted as plain text, like `=1+1` or `"1+1"`.
+    Quoted,
+    /// It was created as a regex or literal explicitly, e.g. with `MaybeRegex::literal`.
+    Explicit,
+}
+
+/// How the input was classified, for showing users e.g. "interpreted as a regex because of
+/// `[0-9]`".
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DetectionReport {
+    pub is_regex: bool,
+    pub reason: DetectionReason,
+    /// The first piece of regex syntax in the input, with its byte range. Ranges are in the
+    /// input as given, including any negation marker, like `pattern_tokens`.
+    pub trigger: Option<(String, Range<usize>)>,
+}
+
+impl MaybeRegex {
+    /// Explains why this pattern is or isn't a regex.
+    pub fn detection(&self) -> DetectionReport {
+        let expanded = expand_quoted_spans(&self.original);
+        let s = expanded.as_ref();
+        let reason = match Parser::new().parse(s) {
+            _ if !self.is_regex() && quoted_literal(s).is_some() => DetectionReason::Quoted,
+            _ if self.is_regex() && slash_delimited(s).is_some() => DetectionReason::Delimited,
+            Err(error)
+                if *error.kind() != ErrorKind::RepetitionMissing && REGEX_REGEX.is_match(s) =>
+            {
+                compile_error(s)
+            }
+            Err(_) => DetectionReason::NoRegexSyntax,
+            Ok(ast) if !uses_regex_syntax(&ast) => DetectionReason::NoRegexSyntax,
+            Ok(ast) if is_question(s, &ast) => DetectionReason::Question,
+            Ok(ast) if repeats_repetition(&ast) => DetectionReason::StackedRepetition,
+            Ok(_) if !self.is_regex() && literal_text(s).is_some() => {
+                DetectionReason::EscapedLiteral
+            }
+            Ok(_) if !self.is_regex() => compile_error(s),
+            Ok(_) => DetectionReason::RegexSyntax,
+        };
+        let detected_regex = matches!(
+            reason,
+            DetectionReason::RegexSyntax | DetectionReason::Delimited
+        );
+        let reason = if detected_regex != self.is_regex() {
+            DetectionReason::Explicit
+        } else {
+            reason
+        };
+
+        let input = self.to_string();
+        let trigger = self
+            .pattern_tokens()
+            .into_iter()
+            .find(|token| !matches!(token.kind, TokenKind::Literal | TokenKind::Negation))
+            .map(|token| (input[token.span.clone()].to_string(), token.span));
+        DetectionReport {
+            is_regex: self.is_regex(),
+            reason,
+            trigger,
+        }
+    }
+
+    /// How confident the classification returned by `is_regex` is.
+    pub fn classification_confidence(&self) -> Confidence {
+        let s = self.original.as_str();
+        match &self.data {
+            TagWrapperData::Regex(_)
+                if STRONG_REGEX_SIGNAL.is_match(s) || slash_delimited(s).is_some() =>
+            {
+                Confidence::Definite
+            }
+            // Only characters that are also common in text, like the dot in a domain name
+            TagWrapperData::Regex(_) if !s.contains(['[', '(', '|', '\\', '{']) => {
+                Confidence::Ambiguous
+            }
+            TagWrapperData::Regex(_) => Confidence::Likely,
+            // Looked like a regex but didn't compile
+            TagWrapperData::Raw(_) if REGEX_REGEX.is_match(s) => Confidence::Ambiguous,
+            TagWrapperData::Raw(_) if s.contains(['?', '|', '(', ')', '{', '}']) => {
+                Confidence::Likely
+            }
+            TagWrapperData::Raw(_) => Confidence::Definite,
+        }
+    }
+}
+
+fn compile_error(s: &str) -> DetectionReason {
+    match RegexBuilder::new(s).build() {
+        Ok(_) => DetectionReason::RegexSyntax,
+        Err(error) => DetectionReason::CompileError(error.to_string()),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn rates_classification() {
+        let confidence = |s: &str| MaybeRegex::new(s).classification_confidence();
+        assert_eq!(confidence("hello world"), Confidence::Definite);
+        assert_eq!(confidence("^error: \\d+$"), Confidence::Definite);
+        assert_eq!(confidence("[a-z]+"), Confidence::Definite);
+        assert_eq!(confidence("(foo|bar)+"), Confidence::Likely);
+        assert_eq!(confidence("is this a regex?"), Confidence::Likely);
+        assert_eq!(confidence("example.com"), Confidence::Ambiguous);
+        assert_eq!(confidence("c++"), Confidence::Ambiguous);
+        assert_eq!(confidence("[unclosed"), Confidence::Ambiguous);
+    }
+
+    #[test]
+    fn explains_detection() {
+        let detection = MaybeRegex::new("-id: [0-9]+").detection();
+        assert!(detection.is_regex);
+        assert_eq!(detection.reason, DetectionReason::RegexSyntax);
+        assert_eq!(detection.trigger, Some(("[0-9]".to_string(), 5..10)));
+
+        let reason = |s: &str| MaybeRegex::new(s).detection().reason;
+        assert_eq!(reason("plain"), DetectionReason::NoRegexSyntax);
+        assert_eq!(reason("is it?"), DetectionReason::Question);
+        assert_eq!(reason("C++"), DetectionReason::StackedRepetition);
+        assert_eq!(reason("a\\.b"), DetectionReason::EscapedLiteral);
+        assert_eq!(reason("=a.b"), DetectionReason::Quoted);
+        assert_eq!(reason("/a/i"), DetectionReason::Delimited);
+        assert!(matches!(reason("[0-9"), DetectionReason::CompileError(_)));
+        assert_eq!(
+            MaybeRegex::literal("a.b").detection().reason,
+            DetectionReason::Explicit
+        );
+        assert_eq!(MaybeRegex::new("plain").detection().trigger, None);
+    }
+}
@@ -0,0 +1,94 @@
+use crate::{MaybeRegex, TagWrapperData};
+use icu_collator::{
+    Collator, CollatorBorrowed,
+    options::{CollatorOptions, Strength},
+};
+use icu_locale_core::Locale;
+use std::cmp::Ordering;
+
+/// A locale's rules for which text counts as equal, for `MaybeRegex::matches_collated`, e.g.
+/// German phone book order where `ö` equals `oe`. Case and accents are ignored wherever the
+/// locale doesn't treat them as different letters.
+#[derive(Debug)]
+pub struct Collation {
+    collator: CollatorBorrowed<'static>,
+}
+
+impl Collation {
+    /// A BCP 47 locale like `de`, `sv` or `de-u-co-phonebk`. `None` if it can't be parsed.
+    pub fn new(locale: &str) -> Option<Self> {
+        let locale: Locale = locale.parse().ok()?;
+        let mut options = CollatorOptions::default();
+        options.strength = Some(Strength::Primary);
+        let collator = Collator::try_new(locale.into(), options).ok()?;
+        Some(Self { collator })
+    }
+
+    fn equal(&self, a: &str, b: &str) -> bool {
+        self.collator.compare(a, b) == Ordering::Equal
+    }
+}
+
+impl MaybeRegex {
+    /// Like `matches`, but plain text matches any part of the haystack that `collation`
+    /// considers equal to it, up to twice the pattern's length, e.g. `Müller` matches
+    /// `MUELLER` in German phone book order. Regexes match as usual.
+    ///
+    /// This compares the pattern against every stretch of the haystack, so it's meant for
+    /// short text like names and titles.
+    ///
+    /// ```
+    /// use maybe_regex::{Collation, MaybeRegex};
+    ///
+    /// let phone_book = Collation::new("de-u-co-phonebk").unwrap();
+    /// assert!(MaybeRegex::new("Müller").matches_collated("Hans Mueller", &phone_book));
+    /// ```
+    pub fn matches_collated<S: AsRef<str>>(&self, haystack: S, collation: &Collation) -> bool {
+        self.is_contained_within_collated(haystack.as_ref(), collation) != self.is_negative
+    }
+
+    fn is_contained_within_collated(&self, haystack: &str, collation: &Collation) -> bool {
+        let TagWrapperData::Raw(needle) = &self.data else {
+            return self.is_contained_within(haystack);
+        };
+        let len = needle.chars().count();
+        if len == 0 {
+            return true;
+        }
+
+        let boundaries: Vec<usize> = haystack
+            .char_indices()
+            .map(|(index, _)| index)
+            .chain([haystack.len()])
+            .collect();
+        (0..boundaries.len()).any(|start| {
+            boundaries[start + 1..]
+                .iter()
+                .take(len * 2)
+                .any(|&end| collation.equal(&haystack[boundaries[start]..end], needle))
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn matches_by_locale_rules() {
+        let phone_book = Collation::new("de-u-co-phonebk").unwrap();
+        let german = Collation::new("de").unwrap();
+        let swedish = Collation::new("sv").unwrap();
+
+        let oe = MaybeRegex::new("Goethe");
+        assert!(oe.matches_collated("Faust by Göthe", &phone_book));
+        assert!(!oe.matches_collated("Faust by Göthe", &german));
+        assert!(MaybeRegex::new("Gothe").matches_collated("Göthe", &german));
+        assert!(!MaybeRegex::new("Gothe").matches_collated("Göthe", &swedish));
+        assert!(MaybeRegex::new("strasse").matches_collated("Hauptstraße 1", &german));
+
+        assert!(!MaybeRegex::new("-Goethe").matches_collated("GOETHE", &german));
+        assert!(MaybeRegex::new("^G.the$").matches_collated("Gothe", &german));
+        assert!(Collation::new("not a locale!").is_none());
+    }
+}
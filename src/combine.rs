@@ -0,0 +1,41 @@
+use crate::MaybeRegex;
+
+/// Whether any of the patterns `matches` the haystack, so a negative pattern counts when the
+/// haystack *doesn't* contain it. False for no patterns.
+///
+/// For the usual "any positive, and no negative" filter, use a `MaybeRegexSet` instead.
+///
+/// ```
+/// use maybe_regex::{MaybeRegex, matches_any};
+///
+/// let patterns = [MaybeRegex::new("error"), MaybeRegex::new("-debug")];
+/// assert!(matches_any(&patterns, "info"));
+/// assert!(!matches_any(&patterns, "debug"));
+/// ```
+pub fn matches_any<S: AsRef<str>>(patterns: &[MaybeRegex], haystack: S) -> bool {
+    let haystack = haystack.as_ref();
+    patterns.iter().any(|pattern| pattern.matches(haystack))
+}
+
+/// Whether every pattern `matches` the haystack, so it must contain each positive pattern and
+/// none of the negative ones. True for no patterns.
+pub fn matches_all<S: AsRef<str>>(patterns: &[MaybeRegex], haystack: S) -> bool {
+    let haystack = haystack.as_ref();
+    patterns.iter().all(|pattern| pattern.matches(haystack))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn honors_negative_patterns() {
+        let patterns = ["error", "disk", "-ignored"].map(MaybeRegex::new);
+        assert!(matches_all(&patterns, "error: disk full"));
+        assert!(!matches_all(&patterns, "error: disk full (ignored)"));
+        assert!(!matches_all(&patterns, "error: out of memory"));
+        assert!(matches_any(&patterns, "all good"));
+        assert!(!matches_any(&patterns[..2], "all good"));
+        assert!(matches_all(&[], "x") && !matches_any(&[], "x"));
+    }
+}
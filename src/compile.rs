@@ -0,0 +1,230 @@
+use crate::{
+    MaybeRegex, TagWrapperData,
+    scratch::{Folding, with_prepared},
+};
+use aho_corasick::AhoCorasick;
+use regex_automata::{
+    Input, MatchKind, PatternSet,
+    meta::{self, Regex},
+    util::syntax,
+};
+
+/// Used when a set hasn't been given an explicit budget.
+pub const DEFAULT_MEMORY_BUDGET: usize = 10 << 20;
+
+/// How much memory compiling a `MaybeRegexSet` took, and whether it fit in the budget. When it
+/// didn't, the set falls back to checking each pattern in turn.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CompileReport {
+    pub budget: usize,
+    /// Heap usage of the Aho-Corasick automata for plain-text patterns.
+    pub literal_bytes: usize,
+    /// Heap usage of the combined regex programs.
+    pub regex_bytes: usize,
+    pub compiled: bool,
+}
+
+impl CompileReport {
+    pub fn total_bytes(&self) -> usize {
+        self.literal_bytes + self.regex_bytes
+    }
+}
+
+// Patterns sharing a case, ASCII and line-ending mode, compiled into one literal automaton and
+// one multi-regex. `ids` map each compiled pattern back to its index in the set.
+#[derive(Debug, Clone)]
+struct Group {
+    case_sensitive: bool,
+    // Patterns promised ASCII haystacks, whose regexes are compiled without Unicode and whose
+    // case-insensitive literals only fold ASCII letters, which the automaton handles itself
+    ascii: bool,
+    normalize_line_endings: bool,
+    literals: Option<(AhoCorasick, Vec<usize>)>,
+    regexes: Option<(Regex, Vec<usize>)>,
+}
+
+#[derive(Debug, Clone)]
+pub(crate) struct CompiledSet {
+    groups: Vec<Group>,
+    // Patterns that can't be compiled from their source, like prebuilt regexes, matched one
+    // at a time, with their index in the set
+    separate: Vec<(MaybeRegex, usize)>,
+    is_negative: Vec<bool>,
+}
+
+impl CompiledSet {
+    /// Returns `None` alongside the report when the patterns don't fit in `budget`.
+    pub(crate) fn build(patterns: &[MaybeRegex], budget: usize) -> (Option<Self>, CompileReport) {
+        let mut report = CompileReport {
+            budget,
+            literal_bytes: 0,
+            regex_bytes: 0,
+            compiled: false,
+        };
+        // Neither automaton knows about custom word characters
+        if patterns
+            .iter()
+            .any(|pattern| !pattern.word_characters.is_empty())
+        {
+            return (None, report);
+        }
+        // Normalizing patterns need the haystack normalized for them alone
+        #[cfg(feature = "normalization")]
+        if patterns
+            .iter()
+            .any(|pattern| pattern.normalization.is_some())
+        {
+            return (None, report);
+        }
+        let mut groups = vec![];
+        for (case_sensitive, ascii, normalize_line_endings) in [
+            (false, false, false),
+            (false, true, false),
+            (true, false, false),
+            (true, true, false),
+            (false, false, true),
+            (false, true, true),
+            (true, false, true),
+            (true, true, true),
+        ] {
+            let mut literals = (vec![], vec![]);
+            let mut regexes = (vec![], vec![]);
+            for (i, pattern) in patterns.iter().enumerate() {
+                if pattern.prebuilt
+                    || pattern.case_sensitive != case_sensitive
+                    || pattern.ascii != ascii
+                    || pattern.normalize_line_endings != normalize_line_endings
+                {
+                    continue;
+                }
+                match &pattern.data {
+                    // Whole-word needles need their boundaries checked, which the regex does
+                    TagWrapperData::Raw(value) if !pattern.whole_word => {
+                        literals.0.push(pattern.needle(value));
+                        literals.1.push(i);
+                    }
+                    _ => {
+                        regexes.0.push(pattern.regex_source());
+                        regexes.1.push(i);
+                    }
+                }
+            }
+
+            let mut group = Group {
+                case_sensitive,
+                ascii,
+                normalize_line_endings,
+                literals: None,
+                regexes: None,
+            };
+            if !literals.0.is_empty() {
+                let Ok(automaton) = AhoCorasick::builder()
+                    .ascii_case_insensitive(ascii && !case_sensitive)
+                    .build(&literals.0)
+                else {
+                    return (None, report);
+                };
+                report.literal_bytes += automaton.memory_usage();
+                group.literals = Some((automaton, literals.1));
+            }
+            if !regexes.0.is_empty() {
+                let remaining = budget.saturating_sub(report.total_bytes());
+                let Ok(regex) = Regex::builder()
+                    // Patterns that can match part of a character, like `(?-u:.)`, are fine when
+                    // only asking which patterns match
+                    .syntax(
+                        syntax::Config::new()
+                            .case_insensitive(!case_sensitive)
+                            .unicode(!ascii)
+                            .utf8(!ascii),
+                    )
+                    .configure(
+                        meta::Config::new()
+                            .match_kind(MatchKind::All)
+                            .nfa_size_limit(Some(remaining)),
+                    )
+                    .build_many(&regexes.0)
+                else {
+                    return (None, report);
+                };
+                report.regex_bytes += regex.memory_usage();
+                group.regexes = Some((regex, regexes.1));
+            }
+            if report.total_bytes() > budget {
+                return (None, report);
+            }
+            if group.literals.is_some() || group.regexes.is_some() {
+                groups.push(group);
+            }
+        }
+
+        report.compiled = true;
+        let compiled = Self {
+            groups,
+            separate: patterns
+                .iter()
+                .enumerate()
+                .filter(|(_, pattern)| pattern.prebuilt)
+                .map(|(i, pattern)| (pattern.clone(), i))
+                .collect(),
+            is_negative: patterns.iter().map(|p| p.is_negative).collect(),
+        };
+        (Some(compiled), report)
+    }
+
+    pub(crate) fn matches(&self, haystack: &str) -> bool {
+        let has_positive = self.is_negative.iter().any(|negative| !negative);
+        let mut any_positive_matched = false;
+        for (contained, negative) in self.contained(haystack).into_iter().zip(&self.is_negative) {
+            match (contained, negative) {
+                (true, true) => return false,
+                (true, false) => any_positive_matched = true,
+                _ => {}
+            }
+        }
+        any_positive_matched || !has_positive
+    }
+
+    /// Whether each pattern, ignoring negation, is contained within `haystack`.
+    pub(crate) fn contained(&self, haystack: &str) -> Vec<bool> {
+        let mut matched = vec![false; self.is_negative.len()];
+        for group in &self.groups {
+            if let Some((automaton, ids)) = &group.literals {
+                let folding = if group.case_sensitive || group.ascii {
+                    Folding::None
+                } else {
+                    Folding::Unicode
+                };
+                with_prepared(
+                    haystack,
+                    folding,
+                    group.normalize_line_endings,
+                    |haystack| {
+                        for found in automaton.find_overlapping_iter(haystack) {
+                            matched[ids[found.pattern().as_usize()]] = true;
+                        }
+                    },
+                );
+            }
+            // Compiled case-insensitive rather than searching a lowercased haystack
+            if let Some((regex, ids)) = &group.regexes {
+                with_prepared(
+                    haystack,
+                    Folding::None,
+                    group.normalize_line_endings,
+                    |haystack| {
+                        let mut found = PatternSet::new(regex.pattern_len());
+                        regex.which_overlapping_matches(&Input::new(haystack), &mut found);
+                        for pattern in found.iter() {
+                            matched[ids[pattern.as_usize()]] = true;
+                        }
+                    },
+                );
+            }
+        }
+        for (pattern, i) in &self.separate {
+            matched[*i] = pattern.is_contained_within(haystack);
+        }
+        matched
+    }
+}
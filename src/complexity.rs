@@ -0,0 +1,121 @@
+use crate::{MaybeRegex, TagWrapperData};
+use regex_automata::{nfa::thompson, util::syntax};
+use regex_syntax::hir::{Hir, HirKind};
+
+/// A rough estimate of how expensive a pattern is to evaluate.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Complexity {
+    /// Relative cost of scanning one haystack byte, where 1.0 is a plain substring search.
+    /// Only meaningful for comparing patterns with each other.
+    pub cost_per_byte: f64,
+    /// Length of the longest literal every match must contain. Longer literals let the
+    /// matcher skip ahead faster.
+    pub literal_len: usize,
+    /// Number of states in the compiled NFA; 0 for plain-text patterns.
+    pub program_size: usize,
+    /// Whether the pattern contains `*`, `+` or `{n,}`.
+    pub unbounded_repetition: bool,
+}
+
+/// Patterns costing more than this per byte are flagged by `Complexity::is_expensive`.
+pub const EXPENSIVE_COST_PER_BYTE: f64 = 8.0;
+
+impl Complexity {
+    pub fn is_expensive(&self) -> bool {
+        self.cost_per_byte > EXPENSIVE_COST_PER_BYTE
+    }
+}
+
+impl MaybeRegex {
+    /// Estimates the cost of evaluating this pattern, so callers can order or budget
+    /// patterns and flag expensive filters.
+    pub fn complexity(&self) -> Complexity {
+        let literal_len = self
+            .required_literals()
+            .iter()
+            .map(String::len)
+            .max()
+            .unwrap_or(0);
+        // Lowercasing the haystack costs about as much again as scanning it
+        let case_folding = if self.case_sensitive { 0.0 } else { 1.0 };
+
+        let TagWrapperData::Regex(regex) = &self.data else {
+            return Complexity {
+                cost_per_byte: 1.0 + case_folding,
+                literal_len,
+                program_size: 0,
+                unbounded_repetition: false,
+            };
+        };
+
+        let case_insensitive = !self.case_sensitive;
+        let program_size = thompson::Compiler::new()
+            .syntax(syntax::Config::new().case_insensitive(case_insensitive))
+            .build(regex.as_str())
+            .map_or(0, |nfa| nfa.states().len());
+        let unbounded_repetition = regex_syntax::ParserBuilder::new()
+            .case_insensitive(case_insensitive)
+            .build()
+            .parse(regex.as_str())
+            .is_ok_and(|hir| has_unbounded_repetition(&hir));
+
+        // Simulating the regex costs more per byte the larger it is. A required literal lets
+        // the prefilter skip most of the haystack, and unbounded repetitions tend to force
+        // slower engines and longer match attempts.
+        let mut cost = 2.0 + (program_size as f64).sqrt();
+        if literal_len > 0 {
+            cost /= 1.0 + (literal_len as f64).ln_1p();
+        }
+        if unbounded_repetition {
+            cost *= 2.0;
+        }
+        Complexity {
+            cost_per_byte: cost.max(1.0) + case_folding,
+            literal_len,
+            program_size,
+            unbounded_repetition,
+        }
+    }
+}
+
+fn has_unbounded_repetition(hir: &Hir) -> bool {
+    match hir.kind() {
+        HirKind::Repetition(repetition) => {
+            repetition.max.is_none() || has_unbounded_repetition(&repetition.sub)
+        }
+        HirKind::Capture(capture) => has_unbounded_repetition(&capture.sub),
+        HirKind::Concat(hirs) | HirKind::Alternation(hirs) => {
+            hirs.iter().any(has_unbounded_repetition)
+        }
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn estimates_cost() {
+        let literal = MaybeRegex::new("error").complexity();
+        assert_eq!(literal.cost_per_byte, 2.0);
+        assert_eq!(literal.literal_len, 5);
+        assert_eq!(
+            MaybeRegex::new("error")
+                .as_case_sensitive()
+                .complexity()
+                .cost_per_byte,
+            1.0
+        );
+
+        let anchored = MaybeRegex::new("^error: [0-9]{3}").complexity();
+        assert!(!anchored.unbounded_repetition);
+        assert!(anchored.program_size > 0);
+
+        let greedy = MaybeRegex::new("(\\w+\\s*)+=.*[0-9]").complexity();
+        assert!(greedy.unbounded_repetition);
+        assert!(greedy.cost_per_byte > anchored.cost_per_byte);
+        assert!(greedy.is_expensive());
+        assert!(!literal.is_expensive());
+    }
+}
@@ -0,0 +1,181 @@
+use crate::MaybeRegex;
+use std::io::{self, BufRead, Write};
+
+/// Which column of a CSV row a `CsvFilter` looks at.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum CsvColumn {
+    /// A column named in the header row.
+    Name(String),
+    Index(usize),
+    /// The row matches if any column does.
+    Any,
+}
+
+/// Streams CSV rows whose column matches a pattern, copying them through unchanged.
+///
+/// Fields may be quoted, with `""` for a literal quote and line breaks allowed inside quotes.
+/// A negative pattern keeps the rows where the column *doesn't* contain it, or, with
+/// `CsvColumn::Any`, where no column does.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CsvFilter {
+    column: CsvColumn,
+    pattern: MaybeRegex,
+    delimiter: char,
+    has_headers: bool,
+}
+
+impl CsvFilter {
+    pub fn new(column: CsvColumn, pattern: MaybeRegex) -> Self {
+        Self {
+            column,
+            pattern,
+            delimiter: ',',
+            has_headers: true,
+        }
+    }
+
+    pub fn with_delimiter(mut self, delimiter: char) -> Self {
+        self.delimiter = delimiter;
+        self
+    }
+
+    /// Treats the first row as data. `CsvColumn::Name` never matches without headers.
+    pub fn without_headers(mut self) -> Self {
+        self.has_headers = false;
+        self
+    }
+
+    /// Copies the header row and every matching row from `reader` to `writer`. Returns how
+    /// many data rows were written. Naming a column that isn't in the header is an
+    /// `InvalidInput` error.
+    pub fn filter<R: BufRead, W: Write>(&self, mut reader: R, mut writer: W) -> io::Result<usize> {
+        let mut index = match &self.column {
+            CsvColumn::Index(index) => Some(*index),
+            _ => None,
+        };
+
+        if self.has_headers {
+            let Some(header) = read_record(&mut reader)? else {
+                return Ok(0);
+            };
+            if let CsvColumn::Name(name) = &self.column {
+                let fields = split_fields(&header, self.delimiter);
+                index = Some(
+                    fields
+                        .iter()
+                        .position(|field| field == name)
+                        .ok_or_else(|| {
+                            io::Error::new(
+                                io::ErrorKind::InvalidInput,
+                                format!("no column named {name}"),
+                            )
+                        })?,
+                );
+            }
+            writer.write_all(header.as_bytes())?;
+        }
+
+        let mut written = 0;
+        while let Some(record) = read_record(&mut reader)? {
+            let fields = split_fields(&record, self.delimiter);
+            let contained = match (&self.column, index) {
+                (CsvColumn::Any, _) => fields
+                    .iter()
+                    .any(|field| self.pattern.is_contained_within(field)),
+                (_, Some(index)) => fields
+                    .get(index)
+                    .is_some_and(|field| self.pattern.is_contained_within(field)),
+                (_, None) => false,
+            };
+            if contained != self.pattern.is_negative {
+                writer.write_all(record.as_bytes())?;
+                written += 1;
+            }
+        }
+        Ok(written)
+    }
+}
+
+// Reads one record, including its line ending, joining lines while inside quotes.
+fn read_record<R: BufRead>(reader: &mut R) -> io::Result<Option<String>> {
+    let mut record = String::new();
+    loop {
+        if reader.read_line(&mut record)? == 0 {
+            return Ok((!record.is_empty()).then_some(record));
+        }
+        if record.matches('"').count().is_multiple_of(2) {
+            return Ok(Some(record));
+        }
+    }
+}
+
+fn split_fields(record: &str, delimiter: char) -> Vec<String> {
+    let record = record.trim_end_matches(['\r', '\n']);
+    let mut fields = vec![];
+    let mut field = String::new();
+    let mut in_quotes = false;
+    let mut chars = record.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if in_quotes && chars.peek() == Some(&'"') => {
+                field.push('"');
+                chars.next();
+            }
+            '"' => in_quotes = !in_quotes,
+            c if c == delimiter && !in_quotes => fields.push(std::mem::take(&mut field)),
+            c => field.push(c),
+        }
+    }
+    fields.push(field);
+    fields
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const PEOPLE: &str = "name,email,notes\r\n\
+        Alice,alice@example.com,\"likes \"\"rust\"\"\"\r\n\
+        Bob,bob@test.org,\"multi\nline, note\"\r\n\
+        Carol,carol@example.com,\r\n";
+
+    fn filtered(filter: CsvFilter) -> (usize, String) {
+        let mut output = vec![];
+        let written = filter.filter(PEOPLE.as_bytes(), &mut output).unwrap();
+        (written, String::from_utf8(output).unwrap())
+    }
+
+    #[test]
+    fn filters_rows_by_column() {
+        let (written, output) = filtered(CsvFilter::new(
+            CsvColumn::Name("email".into()),
+            MaybeRegex::new("@example\\.com$"),
+        ));
+        assert_eq!(written, 2);
+        assert!(output.starts_with("name,email,notes\r\n"));
+        assert!(!output.contains("Bob"));
+
+        let (written, output) = filtered(CsvFilter::new(
+            CsvColumn::Any,
+            MaybeRegex::new("line, note"),
+        ));
+        assert_eq!(written, 1);
+        assert!(output.ends_with("Bob,bob@test.org,\"multi\nline, note\"\r\n"));
+
+        let (written, _) = filtered(CsvFilter::new(
+            CsvColumn::Index(2),
+            MaybeRegex::new("-\"rust\""),
+        ));
+        assert_eq!(written, 2);
+
+        let missing = CsvFilter::new(CsvColumn::Name("phone".into()), MaybeRegex::new("5"))
+            .filter(PEOPLE.as_bytes(), io::sink())
+            .unwrap_err();
+        assert_eq!(missing.kind(), io::ErrorKind::InvalidInput);
+    }
+
+    #[test]
+    fn splits_fields() {
+        assert_eq!(split_fields("a;\"b;c\";\n", ';'), vec!["a", "b;c", ""]);
+    }
+}
@@ -0,0 +1,307 @@
+use crate::REGEX_REGEX;
+use regex_syntax::ast::{
+    self, Ast, ClassSetItem, ErrorKind, Flag, FlagsItemKind, LiteralKind, RepetitionKind,
+    parse::Parser,
+};
+use std::{borrow::Cow, ops::Range};
+
+// Whether input should be compiled as a regex rather than searched for as plain text.
+//
+// Input is a regex when it parses as one and uses regex syntax, e.g. `foo?`, `a{2,3}` or
+// `(x|y)`. Input that doesn't parse is still treated as a (broken) regex when it contains
+// characters that are rarely used otherwise, so e.g. `[0-9` is reported rather than silently
+// searched for, unless it's a repetition with nothing to repeat like `+1`.
+pub(crate) fn looks_like_regex(s: &str) -> bool {
+    match Parser::new().parse(s) {
+        Ok(ast) => uses_regex_syntax(&ast) && !is_question(s, &ast) && !repeats_repetition(&ast),
+        Err(error) => *error.kind() != ErrorKind::RepetitionMissing && REGEX_REGEX.is_match(s),
+    }
+}
+
+// The text of input forced to be plain text with a leading `=` or surrounding double quotes,
+// like ripgrep's `-F`. A lone `=` or `""` is left alone rather than matching everything.
+pub(crate) fn quoted_literal(s: &str) -> Option<&str> {
+    s.strip_prefix('=')
+        .or_else(|| s.strip_prefix('"')?.strip_suffix('"'))
+        .filter(|text| !text.is_empty())
+}
+
+// Input written as `/pattern/flags`, like in vim or JavaScript, split into the pattern and
+// its flags. Only known flags are accepted, so paths like `/usr/bin` aren't mistaken for one.
+pub(crate) fn slash_delimited(s: &str) -> Option<(&str, &str)> {
+    let (pattern, flags) = s.strip_prefix('/')?.rsplit_once('/')?;
+    (!pattern.is_empty() && flags.chars().all(|flag| SLASH_FLAGS.contains(flag)))
+        .then_some((pattern, flags))
+}
+
+// `g` is accepted for JavaScript users but does nothing, since every match is found anyway.
+pub(crate) const SLASH_FLAGS: &str = "gimsxU";
+
+// Replaces PCRE-style `\Q...\E` spans, which the regex crate doesn't support, with their
+// escaped text, so `\Qfile[1].txt\E` is searched for literally. A `\Q` without an `\E`
+// quotes the rest of the input, as in PCRE.
+pub(crate) fn expand_quoted_spans(s: &str) -> Cow<'_, str> {
+    if !s.contains("\\Q") {
+        return Cow::Borrowed(s);
+    }
+    let mut expanded = String::with_capacity(s.len());
+    let mut rest = s;
+    while let Some(start) = rest.find('\\') {
+        expanded.push_str(&rest[..start]);
+        let escape = &rest[start..];
+        if let Some(quoted) = escape.strip_prefix("\\Q") {
+            let (text, after) = quoted.split_once("\\E").unwrap_or((quoted, ""));
+            expanded.push_str(&regex::escape(text));
+            rest = after;
+        } else {
+            // Keep other escapes, including `\\`, whole so their second character isn't
+            // mistaken for the start of one
+            let len = escape[1..].chars().next().map_or(1, |c| 1 + c.len_utf8());
+            expanded.push_str(&escape[..len]);
+            rest = &escape[len..];
+        }
+    }
+    expanded.push_str(rest);
+    Cow::Owned(expanded)
+}
+
+// The case sensitivity set by inline flags at the very start of a regex, like `(?i)` or
+// `(?-i)`, with `Some(true)` meaning case insensitive.
+pub(crate) fn leading_case_insensitive(s: &str) -> Option<bool> {
+    let ast = Parser::new().parse(s).ok()?;
+    let first = match &ast {
+        Ast::Concat(concat) => concat.asts.first()?,
+        ast => ast,
+    };
+    let Ast::Flags(flags) = first else {
+        return None;
+    };
+    let mut negated = false;
+    let mut case_insensitive = None;
+    for item in &flags.flags.items {
+        match item.kind {
+            FlagsItemKind::Negation => negated = true,
+            FlagsItemKind::Flag(Flag::CaseInsensitive) => case_insensitive = Some(!negated),
+            _ => {}
+        }
+    }
+    case_insensitive
+}
+
+// Whether smart case makes the input case sensitive: it has an uppercase letter, ignoring
+// escapes like `\S` or `\P{L}` in regexes, as in ripgrep.
+pub(crate) fn has_uppercase(s: &str, is_regex: bool) -> bool {
+    if !is_regex {
+        return s.chars().any(char::is_uppercase);
+    }
+    let Ok(ast) = Parser::new().parse(s) else {
+        return s.chars().any(char::is_uppercase);
+    };
+    ast::visit(&ast, UppercaseFinder).unwrap_or(true)
+}
+
+// Stops the walk with an "error" at the first uppercase literal.
+struct UppercaseFinder;
+
+impl ast::Visitor for UppercaseFinder {
+    type Output = bool;
+    type Err = ();
+
+    fn finish(self) -> Result<bool, ()> {
+        Ok(false)
+    }
+
+    fn visit_pre(&mut self, ast: &Ast) -> Result<(), ()> {
+        match ast {
+            Ast::Literal(literal) if literal.c.is_uppercase() => Err(()),
+            _ => Ok(()),
+        }
+    }
+
+    fn visit_class_set_item_pre(&mut self, item: &ClassSetItem) -> Result<(), ()> {
+        match item {
+            ClassSetItem::Literal(literal) if literal.c.is_uppercase() => Err(()),
+            ClassSetItem::Range(range) if range.start.c.is_uppercase() => Err(()),
+            _ => Ok(()),
+        }
+    }
+}
+
+// Every literal character of a regex outside character classes, in order, with where it's
+// written, for rewriting them.
+pub(crate) fn literal_characters(s: &str) -> Option<Vec<(Range<usize>, char)>> {
+    let ast = Parser::new().parse(s).ok()?;
+    let mut literals = ast::visit(&ast, LiteralCollector(vec![])).ok()?;
+    literals.sort_by_key(|(range, _)| range.start);
+    Some(literals)
+}
+
+struct LiteralCollector(Vec<(Range<usize>, char)>);
+
+impl ast::Visitor for LiteralCollector {
+    type Output = Vec<(Range<usize>, char)>;
+    type Err = ();
+
+    fn finish(self) -> Result<Self::Output, ()> {
+        Ok(self.0)
+    }
+
+    fn visit_pre(&mut self, ast: &Ast) -> Result<(), ()> {
+        if let Ast::Literal(literal) = ast {
+            self.0.push((
+                literal.span.start.offset..literal.span.end.offset,
+                literal.c,
+            ));
+        }
+        Ok(())
+    }
+}
+
+// The text a regex made only of literals, like `foo\.bar`, matches, so it can be searched for
+// without the regex engine.
+pub(crate) fn literal_text(s: &str) -> Option<String> {
+    let ast = Parser::new().parse(s).ok()?;
+    let asts = match &ast {
+        Ast::Concat(concat) => concat.asts.as_slice(),
+        ast => std::slice::from_ref(ast),
+    };
+    asts.iter()
+        .map(|ast| match ast {
+            Ast::Literal(literal) => Some(literal.c),
+            _ => None,
+        })
+        .collect()
+}
+
+pub(crate) fn uses_regex_syntax(ast: &Ast) -> bool {
+    match ast {
+        Ast::Empty(_) => false,
+        Ast::Literal(literal) => literal.kind != LiteralKind::Verbatim,
+        Ast::Concat(concat) => concat.asts.iter().any(uses_regex_syntax),
+        _ => true,
+    }
+}
+
+// Something like `C++` or `a**`, which is technically a regex but almost certainly meant as
+// text.
+pub(crate) fn repeats_repetition(ast: &Ast) -> bool {
+    match ast {
+        Ast::Repetition(repetition) => {
+            matches!(*repetition.ast, Ast::Repetition(_)) || repeats_repetition(&repetition.ast)
+        }
+        Ast::Group(group) => repeats_repetition(&group.ast),
+        Ast::Concat(concat) => concat.asts.iter().any(repeats_repetition),
+        Ast::Alternation(alternation) => alternation.asts.iter().any(repeats_repetition),
+        _ => false,
+    }
+}
+
+// Prose ending in a question mark, like `is this a regex?`, whose only regex syntax is the
+// `?` on its last character.
+pub(crate) fn is_question(s: &str, ast: &Ast) -> bool {
+    let Ast::Concat(concat) = ast else {
+        return false;
+    };
+    let Some((Ast::Repetition(last), rest)) = concat.asts.split_last() else {
+        return false;
+    };
+    s.contains(char::is_whitespace)
+        && last.greedy
+        && matches!(last.op.kind, RepetitionKind::ZeroOrOne)
+        && !uses_regex_syntax(&last.ast)
+        && !rest.iter().any(uses_regex_syntax)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn detects_regex_syntax() {
+        for regex in [
+            "foo?",
+            "a{2,3}",
+            "(x|y)",
+            "foo|bar",
+            "a.b",
+            "\\d+",
+            "^x",
+            "foo\\.bar",
+        ] {
+            assert!(looks_like_regex(regex), "{regex}");
+        }
+        for literal in [
+            "C++",
+            "+1",
+            "hello world",
+            "is this a regex?",
+            "(unclosed",
+            "",
+            "a-b",
+        ] {
+            assert!(!looks_like_regex(literal), "{literal}");
+        }
+        // Broken regexes are still regexes, so the error gets reported
+        assert!(looks_like_regex("[0-9"));
+    }
+
+    #[test]
+    fn extracts_literal_text() {
+        assert_eq!(literal_text("foo\\.bar"), Some("foo.bar".into()));
+        assert_eq!(literal_text("\\$5 \\(each\\)"), Some("$5 (each)".into()));
+        assert_eq!(literal_text("foo.bar"), None);
+        assert_eq!(literal_text("[a]"), None);
+    }
+
+    #[test]
+    fn splits_slash_delimited() {
+        assert_eq!(slash_delimited("/foo/i"), Some(("foo", "i")));
+        assert_eq!(slash_delimited("/a/b/gm"), Some(("a/b", "gm")));
+        assert_eq!(slash_delimited("/x/"), Some(("x", "")));
+        for undelimited in ["/usr/bin", "//i", "/foo", "foo/i", "/"] {
+            assert_eq!(slash_delimited(undelimited), None, "{undelimited}");
+        }
+    }
+
+    #[test]
+    fn expands_quoted_spans() {
+        assert_eq!(
+            expand_quoted_spans("\\Qfile[1].txt\\E"),
+            "file\\[1\\]\\.txt"
+        );
+        assert_eq!(expand_quoted_spans("^\\Qa+b\\E\\d$"), "^a\\+b\\d$");
+        assert_eq!(expand_quoted_spans("x\\Q.*"), "x\\.\\*");
+        assert_eq!(expand_quoted_spans("\\\\Q.\\d"), "\\\\Q.\\d");
+        assert!(matches!(expand_quoted_spans("a.b"), Cow::Borrowed("a.b")));
+    }
+
+    #[test]
+    fn finds_leading_case_flags() {
+        assert_eq!(leading_case_insensitive("(?i)error"), Some(true));
+        assert_eq!(leading_case_insensitive("(?m-i)[A-Z]"), Some(false));
+        assert_eq!(leading_case_insensitive("(?i)"), Some(true));
+        assert_eq!(leading_case_insensitive("(?m)^x"), None);
+        assert_eq!(leading_case_insensitive("x(?i)y"), None);
+        assert_eq!(leading_case_insensitive("(?i:x)"), None);
+    }
+
+    #[test]
+    fn finds_uppercase_for_smart_case() {
+        assert!(has_uppercase("Error", false));
+        assert!(has_uppercase("\\S", false));
+        assert!(!has_uppercase("\\S+\\P{L}\\W", true));
+        assert!(has_uppercase("x[A-Z]", true));
+        assert!(has_uppercase("^Error$", true));
+        assert!(!has_uppercase("error \\d+", true));
+    }
+
+    #[test]
+    fn extracts_quoted_literals() {
+        assert_eq!(quoted_literal("=1+1"), Some("1+1"));
+        assert_eq!(quoted_literal("\"a.b\""), Some("a.b"));
+        assert_eq!(quoted_literal("==x"), Some("=x"));
+        for unquoted in ["=", "\"\"", "\"", "\"open", "a=b", "1+1"] {
+            assert_eq!(quoted_literal(unquoted), None, "{unquoted}");
+        }
+    }
+}
@@ -0,0 +1,154 @@
+use crate::{MaybeRegex, detect::literal_characters};
+use lazy_static::lazy_static;
+use std::collections::HashMap;
+use unicode_normalization::{UnicodeNormalization, char::is_combining_mark};
+
+// Latin letters whose diacritic is part of the letter, so decomposing them leaves them as
+// they are, by the letter they're written without.
+const UNDECOMPOSABLE: &[(char, &str)] = &[
+    ('d', "đ"),
+    ('h', "ħ"),
+    ('i', "ı"),
+    ('l', "ŀł"),
+    ('o', "ø"),
+    ('t', "ŧ"),
+];
+
+lazy_static! {
+    // Lowercase Latin letters written with diacritics, by the letter they're written without.
+    static ref VARIANTS: HashMap<char, Vec<char>> = {
+        let mut variants: HashMap<char, Vec<char>> = HashMap::new();
+        let latin = ('\u{c0}'..='\u{24f}').chain('\u{1e00}'..='\u{1eff}');
+        for c in latin.filter(|c| c.is_lowercase()) {
+            if let Some(base) = base_letter(c)
+                && base != c
+            {
+                variants.entry(base).or_default().push(c);
+            }
+        }
+        for (base, letters) in UNDECOMPOSABLE {
+            variants.entry(*base).or_default().extend(letters.chars());
+        }
+        variants
+    };
+}
+
+impl MaybeRegex {
+    /// Ignores diacritics, so `resume` matches `résumé` and `résumé` matches `resume`, whether
+    /// the accents are precomposed or written as combining marks. Letters inside regex
+    /// classes like `[e]` are left alone.
+    ///
+    /// The pattern becomes a regex, with each Latin letter matching its accented forms.
+    ///
+    /// ```
+    /// use maybe_regex::MaybeRegex;
+    ///
+    /// let pattern = MaybeRegex::new("resume").as_diacritic_insensitive();
+    /// assert!(pattern.matches("Résumé attached"));
+    /// assert!(pattern.matches("re\u{301}sume\u{301}"));
+    /// ```
+    pub fn as_diacritic_insensitive(self) -> Self {
+        self.wrapped(fold_diacritics)
+    }
+}
+
+// Rewrites each literal letter in a regex into a group matching it with or without
+// diacritics. Regexes that don't parse are left for the regex engine to report.
+fn fold_diacritics(pattern: &str) -> String {
+    let Some(literals) = literal_characters(pattern) else {
+        return pattern.to_string();
+    };
+    let mut output = String::with_capacity(pattern.len());
+    let mut last = 0;
+    for (range, c) in literals {
+        // Combining marks are already matched after each letter
+        let replacement = if is_combining_mark(c) {
+            Some(String::new())
+        } else {
+            variant_group(c)
+        };
+        if let Some(group) = replacement {
+            output.push_str(&pattern[last..range.start]);
+            output.push_str(&group);
+            last = range.end;
+        }
+    }
+    output.push_str(&pattern[last..]);
+    output
+}
+
+// A group matching `c`, or the letter it's an accented form of, with any diacritic, e.g.
+// `(?:[eèéêë…]\p{Mn}*)` for `é`. Uppercase letters give uppercase forms.
+fn variant_group(c: char) -> Option<String> {
+    let lower = c.to_lowercase().next()?;
+    let base = match UNDECOMPOSABLE
+        .iter()
+        .find(|(_, letters)| letters.contains(lower))
+    {
+        Some((base, _)) => *base,
+        None => base_letter(lower)?,
+    };
+    let variants = VARIANTS.get(&base)?;
+    let mut group = String::from("(?:[");
+    for variant in std::iter::once(base).chain(variants.iter().copied()) {
+        if c.is_uppercase() {
+            group.extend(variant.to_uppercase());
+        } else {
+            group.push(variant);
+        }
+    }
+    group.push_str(r"]\p{Mn}*)");
+    Some(group)
+}
+
+// The letter `c` is written with once decomposed and stripped of combining marks, if that's a
+// single letter.
+fn base_letter(c: char) -> Option<char> {
+    let mut letters = std::iter::once(c).nfd().filter(|&c| !is_combining_mark(c));
+    let base = letters.next()?;
+    letters.next().is_none().then_some(base)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn ignores_diacritics() {
+        let resume = MaybeRegex::new("resume").as_diacritic_insensitive();
+        assert!(resume.matches("RÉSUMÉ") && resume.matches("re\u{301}sume\u{301}"));
+        assert!(!resume.matches("resumption"));
+        assert!(
+            MaybeRegex::new("résumé")
+                .as_diacritic_insensitive()
+                .matches("resume")
+        );
+
+        let regex = MaybeRegex::new("^cafe\\b").as_diacritic_insensitive();
+        assert!(regex.matches("Café au lait") && !regex.matches("a café"));
+        assert!(
+            !MaybeRegex::new("caf[e]")
+                .as_diacritic_insensitive()
+                .matches("café")
+        );
+
+        let vietnamese = MaybeRegex::new("ban tin").as_diacritic_insensitive();
+        assert!(vietnamese.matches("Bản tin") && vietnamese.matches("bạn tìn"));
+        for (pattern, haystack) in [("a", "ǎ"), ("e", "ế"), ("n", "ǹ"), ("łodz", "Lodz")] {
+            let pattern = MaybeRegex::new(pattern).as_diacritic_insensitive();
+            assert!(pattern.matches(haystack), "{haystack}");
+        }
+        let marked = MaybeRegex::new("re\u{301}sume\u{301}").as_diacritic_insensitive();
+        assert!(marked.matches("resume") && marked.matches("résumé"));
+
+        let upper = MaybeRegex::new("Ecole")
+            .as_case_sensitive()
+            .as_diacritic_insensitive();
+        assert!(upper.matches("École") && !upper.matches("école"));
+        assert!(
+            !MaybeRegex::new("-cafe")
+                .as_diacritic_insensitive()
+                .matches("café")
+        );
+    }
+}
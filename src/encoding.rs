@@ -0,0 +1,174 @@
+use std::io::{self, BufRead};
+#[cfg(feature = "encoding")]
+use std::io::{BufReader, Read};
+
+/// How a `Searcher` decodes its input.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Encoding {
+    /// Sniffs a byte-order mark: a UTF-8 BOM is skipped, and with the `encoding` feature,
+    /// UTF-16 input with a BOM is transcoded. Anything else is read as UTF-8.
+    #[default]
+    Auto,
+    /// UTF-8, skipping a leading BOM if there is one.
+    Utf8,
+    #[cfg(feature = "encoding")]
+    Utf16Le,
+    #[cfg(feature = "encoding")]
+    Utf16Be,
+}
+
+const UTF8_BOM: &[u8] = &[0xEF, 0xBB, 0xBF];
+#[cfg(feature = "encoding")]
+const UTF16_LE_BOM: &[u8] = &[0xFF, 0xFE];
+#[cfg(feature = "encoding")]
+const UTF16_BE_BOM: &[u8] = &[0xFE, 0xFF];
+
+/// Wraps `reader` so it yields UTF-8 with any byte-order mark removed.
+pub(crate) fn decode<'r, R: BufRead + 'r>(
+    mut reader: R,
+    encoding: Encoding,
+) -> io::Result<Box<dyn BufRead + 'r>> {
+    let start = reader.fill_buf()?;
+    let (encoding, bom_len) = match encoding {
+        Encoding::Auto if start.starts_with(UTF8_BOM) => (Encoding::Utf8, UTF8_BOM.len()),
+        #[cfg(feature = "encoding")]
+        Encoding::Auto if start.starts_with(UTF16_LE_BOM) => (Encoding::Utf16Le, 2),
+        #[cfg(feature = "encoding")]
+        Encoding::Auto if start.starts_with(UTF16_BE_BOM) => (Encoding::Utf16Be, 2),
+        Encoding::Auto => (Encoding::Utf8, 0),
+        Encoding::Utf8 if start.starts_with(UTF8_BOM) => (encoding, UTF8_BOM.len()),
+        #[cfg(feature = "encoding")]
+        Encoding::Utf16Le if start.starts_with(UTF16_LE_BOM) => (encoding, 2),
+        #[cfg(feature = "encoding")]
+        Encoding::Utf16Be if start.starts_with(UTF16_BE_BOM) => (encoding, 2),
+        _ => (encoding, 0),
+    };
+    reader.consume(bom_len);
+
+    Ok(match encoding {
+        Encoding::Auto | Encoding::Utf8 => Box::new(reader),
+        #[cfg(feature = "encoding")]
+        Encoding::Utf16Le => Box::new(BufReader::new(Transcoder::new(
+            reader,
+            encoding_rs::UTF_16LE,
+        ))),
+        #[cfg(feature = "encoding")]
+        Encoding::Utf16Be => Box::new(BufReader::new(Transcoder::new(
+            reader,
+            encoding_rs::UTF_16BE,
+        ))),
+    })
+}
+
+// Transcodes to UTF-8 as it's read. Invalid sequences become U+FFFD.
+#[cfg(feature = "encoding")]
+struct Transcoder<R> {
+    inner: R,
+    decoder: encoding_rs::Decoder,
+    output: String,
+    position: usize,
+    finished: bool,
+}
+
+#[cfg(feature = "encoding")]
+impl<R: BufRead> Transcoder<R> {
+    fn new(inner: R, encoding: &'static encoding_rs::Encoding) -> Self {
+        Self {
+            inner,
+            // The BOM was already consumed by `decode`
+            decoder: encoding.new_decoder_without_bom_handling(),
+            output: String::new(),
+            position: 0,
+            finished: false,
+        }
+    }
+}
+
+#[cfg(feature = "encoding")]
+impl<R: BufRead> Read for Transcoder<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        while self.position == self.output.len() {
+            if self.finished {
+                return Ok(0);
+            }
+            self.output.clear();
+            self.position = 0;
+
+            let input = self.inner.fill_buf()?;
+            let last = input.is_empty();
+            let needed = self
+                .decoder
+                .max_utf8_buffer_length(input.len())
+                .unwrap_or(input.len() * 3 + 4);
+            self.output.reserve(needed);
+            let (_, read, _) = self.decoder.decode_to_string(input, &mut self.output, last);
+            self.inner.consume(read);
+            self.finished = last;
+        }
+
+        let output = self.output.as_bytes();
+        let len = buf.len().min(output.len() - self.position);
+        buf[..len].copy_from_slice(&output[self.position..self.position + len]);
+        self.position += len;
+        Ok(len)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::io::Read;
+
+    fn decoded(bytes: &[u8], encoding: Encoding) -> String {
+        let mut output = String::new();
+        decode(bytes, encoding)
+            .unwrap()
+            .read_to_string(&mut output)
+            .unwrap();
+        output
+    }
+
+    #[cfg(feature = "encoding")]
+    fn utf16(text: &str, big_endian: bool) -> Vec<u8> {
+        let mut bytes = if big_endian {
+            UTF16_BE_BOM.to_vec()
+        } else {
+            UTF16_LE_BOM.to_vec()
+        };
+        for unit in text.encode_utf16() {
+            bytes.extend(if big_endian {
+                unit.to_be_bytes()
+            } else {
+                unit.to_le_bytes()
+            });
+        }
+        bytes
+    }
+
+    #[test]
+    fn decodes_boms() {
+        let text = "héllo\r\nwörld 🦀\n";
+        assert_eq!(
+            decoded(&[UTF8_BOM, text.as_bytes()].concat(), Encoding::Auto),
+            text
+        );
+        assert_eq!(decoded(text.as_bytes(), Encoding::Auto), text);
+        assert_eq!(
+            decoded(&[UTF8_BOM, text.as_bytes()].concat(), Encoding::Utf8),
+            text
+        );
+    }
+
+    #[cfg(feature = "encoding")]
+    #[test]
+    fn transcodes_utf16() {
+        let text = "héllo\r\nwörld 🦀\n";
+        assert_eq!(decoded(&utf16(text, false), Encoding::Auto), text);
+        assert_eq!(decoded(&utf16(text, true), Encoding::Auto), text);
+        assert_eq!(decoded(&utf16(text, true)[2..], Encoding::Utf16Be), text);
+        assert_eq!(
+            decoded(&[0x41, 0x00, 0x00, 0xD8], Encoding::Utf16Le),
+            "A\u{FFFD}"
+        );
+    }
+}
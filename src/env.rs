@@ -0,0 +1,115 @@
+use crate::MaybeRegex;
+
+impl MaybeRegex {
+    /// Like `new`, but first expands `$VAR` and `${VAR}` references from the environment.
+    ///
+    /// `$$` produces a literal `$`. Unset variables, and any `$` that isn't followed by
+    /// a variable name (such as a trailing regex anchor), are left untouched. Values are
+    /// matched as written, so a path like `/tmp/a+b` neither breaks a regex nor turns plain
+    /// text into one.
+    pub fn new_expanded<S: AsRef<str>>(s: S) -> Self {
+        Self::from(expand_pattern_with(s.as_ref(), |name| {
+            std::env::var(name).ok()
+        }))
+    }
+}
+
+// Expands variables in a pattern, escaping their values if it's a regex with or without them,
+// so they match as written. Escaped values in plain text are still searched for as plain text.
+pub(crate) fn expand_pattern_with(s: &str, lookup: impl Fn(&str) -> Option<String>) -> String {
+    let template = expand_vars_with(s, |name| lookup(name).map(|_| String::new()));
+    let expanded = expand_vars_with(s, &lookup);
+    if MaybeRegex::new(template).is_regex() || MaybeRegex::new(&expanded).is_regex() {
+        expand_vars_with(s, |name| lookup(name).map(|value| regex::escape(&value)))
+    } else {
+        expanded
+    }
+}
+
+pub(crate) fn expand_vars_with(s: &str, lookup: impl Fn(&str) -> Option<String>) -> String {
+    let mut output = String::with_capacity(s.len());
+    let mut rest = s;
+    while let Some(index) = rest.find('$') {
+        output.push_str(&rest[..index]);
+        let after = &rest[index + 1..];
+
+        if let Some(after) = after.strip_prefix('$') {
+            output.push('$');
+            rest = after;
+            continue;
+        }
+
+        let (name, consumed) = match after.strip_prefix('{') {
+            Some(braced) => match braced.find('}') {
+                Some(end) if is_var_name(&braced[..end]) => (&braced[..end], end + 2),
+                _ => ("", 0),
+            },
+            None => {
+                let len = var_name_len(after);
+                (&after[..len], len)
+            }
+        };
+
+        match lookup(name).filter(|_| !name.is_empty()) {
+            Some(value) => output.push_str(&value),
+            None => {
+                output.push('$');
+                output.push_str(&after[..consumed]);
+            }
+        }
+        rest = &after[consumed..];
+    }
+    output.push_str(rest);
+    output
+}
+
+fn var_name_len(s: &str) -> usize {
+    if !s.starts_with(|c: char| c.is_ascii_alphabetic() || c == '_') {
+        return 0;
+    }
+    s.find(|c: char| !(c.is_ascii_alphanumeric() || c == '_'))
+        .unwrap_or(s.len())
+}
+
+fn is_var_name(s: &str) -> bool {
+    !s.is_empty() && var_name_len(s) == s.len()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn lookup(name: &str) -> Option<String> {
+        match name {
+            "TMPDIR" => Some("/tmp".into()),
+            "USER" => Some("alice".into()),
+            "DIR" => Some("/tmp/a+b".into()),
+            _ => None,
+        }
+    }
+
+    #[test]
+    fn expands_variables() {
+        assert_eq!(expand_vars_with("-$TMPDIR/.*", lookup), "-/tmp/.*");
+        assert_eq!(expand_vars_with("${USER}_logs", lookup), "alice_logs");
+        assert_eq!(expand_vars_with("$USER$TMPDIR", lookup), "alice/tmp");
+    }
+
+    #[test]
+    fn leaves_anchors_escapes_and_unknowns_alone() {
+        assert_eq!(expand_vars_with("o$", lookup), "o$");
+        assert_eq!(expand_vars_with("cost: $$5", lookup), "cost: $5");
+        assert_eq!(expand_vars_with("$NOPE/${NOPE}", lookup), "$NOPE/${NOPE}");
+        assert_eq!(expand_vars_with("${unclosed", lookup), "${unclosed");
+    }
+
+    #[test]
+    fn escapes_values_in_regexes() {
+        let expanded = expand_pattern_with("^$DIR/.*\\.log$", lookup);
+        assert_eq!(expanded, "^/tmp/a\\+b/.*\\.log$");
+        assert!(MaybeRegex::new(expanded).matches("/tmp/a+b/x.log"));
+        let plain = MaybeRegex::new(expand_pattern_with("$DIR logs", lookup));
+        assert!(!plain.is_regex() && plain.matches("/tmp/a+b logs"));
+        assert_eq!(expand_pattern_with("$USER logs", lookup), "alice logs");
+    }
+}
@@ -0,0 +1,110 @@
+use std::{fmt::Display, ops::Range};
+
+/// Why a pattern couldn't be created.
+#[derive(Debug, Clone, PartialEq)]
+#[non_exhaustive]
+pub enum MaybeRegexError {
+    /// The input looked like a regex but didn't compile. This includes regexes whose compiled
+    /// form would exceed the size limit.
+    InvalidRegex {
+        pattern: String,
+        error: regex::Error,
+    },
+    /// Nothing was left after removing the negation marker, and the builder disallows empty
+    /// patterns.
+    EmptyPattern,
+    /// The input was longer than the builder's `max_len`, in bytes.
+    PatternTooLong { len: usize, max_len: usize },
+}
+
+impl Display for MaybeRegexError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::InvalidRegex { pattern, error } => {
+                write!(f, "Invalid regex {pattern:?}: {error}")
+            }
+            Self::EmptyPattern => write!(f, "Pattern is empty"),
+            Self::PatternTooLong { len, max_len } => {
+                write!(
+                    f,
+                    "Pattern is {len} bytes long, over the limit of {max_len}"
+                )
+            }
+        }
+    }
+}
+
+impl std::error::Error for MaybeRegexError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::InvalidRegex { error, .. } => Some(error),
+            _ => None,
+        }
+    }
+}
+
+/// Why an input failed `MaybeRegex::validate`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ValidationError {
+    /// The input doesn't contain the required (positive) pattern.
+    MissingRequired { pattern: String },
+    /// The input contains a blocked (negative) pattern, first found at `span`.
+    Blocked { pattern: String, span: Range<usize> },
+}
+
+impl Display for ValidationError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::MissingRequired { pattern } => write!(f, "Input must contain {pattern:?}"),
+            Self::Blocked { pattern, span } => write!(
+                f,
+                "Input contains blocked pattern {pattern:?} at {}..{}",
+                span.start, span.end
+            ),
+        }
+    }
+}
+
+impl std::error::Error for ValidationError {}
+
+/// Why `MaybeRegexSetBuilder::build` rejected a set.
+#[derive(Debug, Clone, PartialEq)]
+pub enum SetBuildError {
+    /// A pattern couldn't be created, under `FallbackPolicy::Error` or because it was empty.
+    InvalidPattern {
+        pattern: String,
+        error: MaybeRegexError,
+    },
+    /// The same pattern, with the same negation, was added more than once.
+    Duplicate { pattern: String },
+    /// The same pattern was added as both a positive and a negative, so nothing can match.
+    Conflict { pattern: String },
+    /// A weight was negative or not a number.
+    InvalidWeight { pattern: String },
+}
+
+impl Display for SetBuildError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::InvalidPattern { pattern, error } => {
+                write!(f, "Pattern {pattern:?} is invalid: {error}")
+            }
+            Self::Duplicate { pattern } => write!(f, "Pattern {pattern:?} was added twice"),
+            Self::Conflict { pattern } => {
+                write!(f, "Pattern {pattern:?} is both required and blocked")
+            }
+            Self::InvalidWeight { pattern } => {
+                write!(f, "Pattern {pattern:?} has an invalid weight")
+            }
+        }
+    }
+}
+
+impl std::error::Error for SetBuildError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::InvalidPattern { error, .. } => Some(error),
+            _ => None,
+        }
+    }
+}
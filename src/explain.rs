@@ -0,0 +1,243 @@
+use crate::{MaybeRegex, TagWrapperData};
+use regex_syntax::ast::{
+    self, Assertion, AssertionKind, Ast, ClassPerlKind, RepetitionKind, RepetitionRange,
+};
+use std::fmt::Display;
+
+/// A plain-language description of how a `MaybeRegex` will be applied.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Explanation {
+    pub is_regex: bool,
+    pub is_negative: bool,
+    pub case_sensitive: bool,
+    pub anchored_start: bool,
+    pub anchored_end: bool,
+    /// One description per top-level element of the pattern, in order.
+    pub parts: Vec<String>,
+}
+
+impl Display for Explanation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let kind = if self.is_regex {
+            "a regex"
+        } else {
+            "plain text"
+        };
+        write!(
+            f,
+            "Treated as {kind}. Looks for {}.",
+            self.parts.join(", then ")
+        )?;
+        if self.case_sensitive {
+            write!(f, " Case-sensitive.")?;
+        } else {
+            write!(f, " Case-insensitive.")?;
+        }
+        if self.is_negative {
+            write!(
+                f,
+                " Negated: only haystacks that don't contain this will match."
+            )?;
+        }
+        Ok(())
+    }
+}
+
+impl MaybeRegex {
+    pub fn explain(&self) -> String {
+        self.explanation().to_string()
+    }
+
+    pub fn explanation(&self) -> Explanation {
+        let mut explanation = Explanation {
+            is_regex: self.is_regex(),
+            is_negative: self.is_negative,
+            case_sensitive: self.case_sensitive,
+            anchored_start: false,
+            anchored_end: false,
+            parts: vec![],
+        };
+
+        let parsed = match &self.data {
+            TagWrapperData::Raw(_) => None,
+            TagWrapperData::Regex(regex) => ast::parse::Parser::new().parse(regex.as_str()).ok(),
+        };
+        match parsed {
+            Some(ast) => {
+                let items = match &ast {
+                    Ast::Concat(concat) => concat.asts.iter().collect(),
+                    ast => vec![ast],
+                };
+                explanation.anchored_start = items.first().is_some_and(|ast| is_start(ast));
+                explanation.anchored_end = items.last().is_some_and(|ast| is_end(ast));
+                explanation.parts = describe_sequence(&items);
+            }
+            None => explanation
+                .parts
+                .push(format!("the text {:?}", self.original)),
+        }
+        explanation
+    }
+}
+
+fn is_start(ast: &Ast) -> bool {
+    matches!(
+        ast,
+        Ast::Assertion(a) if matches!(a.kind, AssertionKind::StartLine | AssertionKind::StartText)
+    )
+}
+
+fn is_end(ast: &Ast) -> bool {
+    matches!(
+        ast,
+        Ast::Assertion(a) if matches!(a.kind, AssertionKind::EndLine | AssertionKind::EndText)
+    )
+}
+
+// Consecutive literals are merged so "foo" reads as one piece of text rather than three characters.
+fn describe_sequence(items: &[&Ast]) -> Vec<String> {
+    let mut parts = vec![];
+    let mut text = String::new();
+    for ast in items {
+        if let Ast::Literal(literal) = ast {
+            text.push(literal.c);
+            continue;
+        }
+        if !text.is_empty() {
+            parts.push(format!("the text {:?}", std::mem::take(&mut text)));
+        }
+        if let Some(description) = describe(ast) {
+            parts.push(description);
+        }
+    }
+    if !text.is_empty() {
+        parts.push(format!("the text {text:?}"));
+    }
+    if parts.is_empty() {
+        parts.push("anything".into());
+    }
+    parts
+}
+
+fn describe(ast: &Ast) -> Option<String> {
+    let description = match ast {
+        Ast::Empty(_) | Ast::Flags(_) => return None,
+        Ast::Literal(literal) => format!("the character {:?}", literal.c),
+        Ast::Dot(_) => "any character".into(),
+        Ast::Assertion(assertion) => describe_assertion(assertion).into(),
+        Ast::ClassPerl(class) => {
+            let name = match class.kind {
+                ClassPerlKind::Digit => "a digit",
+                ClassPerlKind::Space => "whitespace",
+                ClassPerlKind::Word => "a word character",
+            };
+            if class.negated {
+                format!("anything except {name}")
+            } else {
+                name.into()
+            }
+        }
+        Ast::ClassUnicode(class) => format!("a character from the Unicode class {:?}", class.kind),
+        Ast::ClassBracketed(class) => {
+            let mut description = String::new();
+            let printed = ast::print::Printer::new()
+                .print(&Ast::ClassBracketed(class.clone()), &mut description);
+            if printed.is_err() {
+                description = "[...]".into();
+            }
+            if class.negated {
+                format!("a character not in {description}")
+            } else {
+                format!("a character in {description}")
+            }
+        }
+        Ast::Repetition(repetition) => {
+            let inner = describe(&repetition.ast).unwrap_or_else(|| "nothing".into());
+            let count = match &repetition.op.kind {
+                RepetitionKind::ZeroOrOne => "optionally".into(),
+                RepetitionKind::ZeroOrMore => "repeated zero or more times".into(),
+                RepetitionKind::OneOrMore => "repeated one or more times".into(),
+                RepetitionKind::Range(RepetitionRange::Exactly(n)) => {
+                    format!("repeated exactly {n} times")
+                }
+                RepetitionKind::Range(RepetitionRange::AtLeast(n)) => {
+                    format!("repeated at least {n} times")
+                }
+                RepetitionKind::Range(RepetitionRange::Bounded(min, max)) => {
+                    format!("repeated between {min} and {max} times")
+                }
+            };
+            format!("{inner} ({count})")
+        }
+        Ast::Group(group) => {
+            let items = match group.ast.as_ref() {
+                Ast::Concat(concat) => concat.asts.iter().collect(),
+                ast => vec![ast],
+            };
+            format!("({})", describe_sequence(&items).join(", then "))
+        }
+        Ast::Alternation(alternation) => {
+            let options: Vec<String> = alternation
+                .asts
+                .iter()
+                .map(|ast| match ast {
+                    Ast::Concat(concat) => {
+                        describe_sequence(&concat.asts.iter().collect::<Vec<_>>())
+                    }
+                    ast => describe_sequence(&[ast]),
+                })
+                .map(|parts| parts.join(", then "))
+                .collect();
+            format!("either {}", options.join(" or "))
+        }
+        Ast::Concat(concat) => {
+            describe_sequence(&concat.asts.iter().collect::<Vec<_>>()).join(", then ")
+        }
+    };
+    Some(description)
+}
+
+fn describe_assertion(assertion: &Assertion) -> &'static str {
+    match assertion.kind {
+        AssertionKind::StartLine | AssertionKind::StartText => "the start of the text",
+        AssertionKind::EndLine | AssertionKind::EndText => "the end of the text",
+        AssertionKind::WordBoundary => "a word boundary",
+        AssertionKind::NotWordBoundary => "a position that isn't a word boundary",
+        AssertionKind::WordBoundaryStart
+        | AssertionKind::WordBoundaryStartAngle
+        | AssertionKind::WordBoundaryStartHalf => "the start of a word",
+        AssertionKind::WordBoundaryEnd
+        | AssertionKind::WordBoundaryEndAngle
+        | AssertionKind::WordBoundaryEndHalf => "the end of a word",
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn explains_regexes() {
+        let explanation = MaybeRegex::new("^error: \\d+").explanation();
+        assert!(explanation.is_regex);
+        assert!(explanation.anchored_start);
+        assert!(!explanation.anchored_end);
+        assert_eq!(
+            explanation.parts,
+            vec![
+                "the start of the text",
+                "the text \"error: \"",
+                "a digit (repeated one or more times)"
+            ]
+        );
+    }
+
+    #[test]
+    fn explains_literals_and_flags() {
+        assert_eq!(
+            MaybeRegex::new("-hello").explain(),
+            "Treated as plain text. Looks for the text \"hello\". Case-insensitive. \
+             Negated: only haystacks that don't contain this will match."
+        );
+    }
+}
@@ -0,0 +1,304 @@
+use crate::{MaybeRegex, TagWrapperData};
+#[cfg(feature = "serde")]
+use serde::de::{self, DeserializeOwned, IntoDeserializer, Visitor, value::MapDeserializer};
+use std::collections::HashMap;
+
+impl MaybeRegex {
+    /// The text of every match, in order.
+    pub fn extract_all<'h>(&self, text: &'h str) -> Vec<&'h str> {
+        self.spans(text)
+            .into_iter()
+            .map(|range| &text[range])
+            .collect()
+    }
+
+    /// Like `extract_all`, but each distinct match is only returned the first time it's seen.
+    pub fn extract_unique<'h>(&self, text: &'h str) -> Vec<&'h str> {
+        let mut unique: Vec<&str> = vec![];
+        for matched in self.extract_all(text) {
+            if !unique.contains(&matched) {
+                unique.push(matched);
+            }
+        }
+        unique
+    }
+
+    /// The text between matches, like `str::split`. Negation is ignored.
+    pub fn split<'h>(&self, text: &'h str) -> Vec<&'h str> {
+        let mut pieces = vec![];
+        let mut last = 0;
+        for range in self.spans(text) {
+            if range.start < last {
+                continue;
+            }
+            pieces.push(&text[last..range.start]);
+            last = range.end;
+        }
+        pieces.push(&text[last..]);
+        pieces
+    }
+
+    /// Like `split`, but without the trailing empty piece when `text` ends with a match,
+    /// matching `str::split_terminator`.
+    pub fn split_terminator<'h>(&self, text: &'h str) -> Vec<&'h str> {
+        let mut pieces = self.split(text);
+        if pieces.last() == Some(&"") {
+            pieces.pop();
+        }
+        pieces
+    }
+
+    /// The names of the regex's named groups, like `level` for `(?P<level>\w+)`, in order.
+    /// Plain text has none.
+    pub fn capture_names(&self) -> Vec<&str> {
+        match &self.data {
+            TagWrapperData::Regex(regex) => regex.capture_names().flatten().collect(),
+            TagWrapperData::Raw(_) => vec![],
+        }
+    }
+
+    /// The text of each named group in the first match, by name, for pulling fields out of
+    /// e.g. log lines. Groups that didn't participate are left out, and no match gives an
+    /// empty map.
+    ///
+    /// ```
+    /// use maybe_regex::MaybeRegex;
+    ///
+    /// let pattern = MaybeRegex::new("(?P<level>[A-Z]+): (?P<message>.*)").as_case_sensitive();
+    /// let fields = pattern.extract_named("12:00 WARN: disk full");
+    /// assert_eq!(fields["level"], "WARN");
+    /// assert_eq!(fields["message"], "disk full");
+    /// ```
+    pub fn extract_named(&self, text: &str) -> HashMap<String, String> {
+        let TagWrapperData::Regex(regex) = &self.data else {
+            return HashMap::new();
+        };
+        let Some(captures) = self.captures(text) else {
+            return HashMap::new();
+        };
+        regex
+            .capture_names()
+            .enumerate()
+            .filter_map(|(i, name)| {
+                Some((name?.to_string(), captures.get(i)?.as_str().to_string()))
+            })
+            .collect()
+    }
+}
+
+#[cfg(feature = "serde")]
+impl MaybeRegex {
+    /// Deserializes every match into a `T`, mapping named capture groups to fields of the
+    /// same name. Captured text is coerced to the field's type, so `(?P<port>[0-9]+)` can fill
+    /// a `u16`. Optional groups that didn't participate in a match are left out, which suits
+    /// `Option` or `#[serde(default)]` fields.
+    pub fn extract_into<T: DeserializeOwned>(
+        &self,
+        text: &str,
+    ) -> Result<Vec<T>, de::value::Error> {
+        let names: Vec<(usize, &str)> = match &self.data {
+            TagWrapperData::Regex(regex) => regex
+                .capture_names()
+                .enumerate()
+                .filter_map(|(i, name)| Some((i, name?)))
+                .collect(),
+            TagWrapperData::Raw(_) => vec![],
+        };
+        let records: Vec<Vec<(&str, &str)>> = self
+            .captures_iter(text)
+            .map(|captures| {
+                names
+                    .iter()
+                    .filter_map(|&(i, name)| Some((name, captures.get(i)?.as_str())))
+                    .collect()
+            })
+            .collect();
+
+        records
+            .into_iter()
+            .map(|fields| {
+                let fields = fields
+                    .into_iter()
+                    .map(|(name, value)| (name, CapturedText(value)));
+                T::deserialize(MapDeserializer::new(fields))
+            })
+            .collect()
+    }
+}
+
+// A single captured string that deserializes into whatever type the field asks for.
+#[cfg(feature = "serde")]
+struct CapturedText<'a>(&'a str);
+
+#[cfg(feature = "serde")]
+impl<'a> IntoDeserializer<'a, de::value::Error> for CapturedText<'a> {
+    type Deserializer = Self;
+
+    fn into_deserializer(self) -> Self {
+        self
+    }
+}
+
+#[cfg(feature = "serde")]
+macro_rules! deserialize_parsed {
+    ($($method:ident => $visit:ident),* $(,)?) => {
+        $(
+            fn $method<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+                match self.0.trim().parse() {
+                    Ok(value) => visitor.$visit(value),
+                    Err(_) => Err(de::Error::invalid_value(de::Unexpected::Str(self.0), &visitor)),
+                }
+            }
+        )*
+    };
+}
+
+#[cfg(feature = "serde")]
+impl<'de> de::Deserializer<'de> for CapturedText<'de> {
+    type Error = de::value::Error;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        visitor.visit_borrowed_str(self.0)
+    }
+
+    fn deserialize_bool<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        match self.0.trim().to_lowercase().as_str() {
+            "true" | "yes" | "y" | "1" | "on" => visitor.visit_bool(true),
+            "false" | "no" | "n" | "0" | "off" => visitor.visit_bool(false),
+            _ => Err(de::Error::invalid_value(
+                de::Unexpected::Str(self.0),
+                &visitor,
+            )),
+        }
+    }
+
+    deserialize_parsed! {
+        deserialize_i8 => visit_i8,
+        deserialize_i16 => visit_i16,
+        deserialize_i32 => visit_i32,
+        deserialize_i64 => visit_i64,
+        deserialize_u8 => visit_u8,
+        deserialize_u16 => visit_u16,
+        deserialize_u32 => visit_u32,
+        deserialize_u64 => visit_u64,
+        deserialize_f32 => visit_f32,
+        deserialize_f64 => visit_f64,
+        deserialize_char => visit_char,
+    }
+
+    fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        if self.0.is_empty() {
+            visitor.visit_none()
+        } else {
+            visitor.visit_some(self)
+        }
+    }
+
+    fn deserialize_enum<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        _variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        visitor.visit_enum(self.0.into_deserializer())
+    }
+
+    serde::forward_to_deserialize_any! {
+        i128 u128 str string bytes byte_buf unit unit_struct newtype_struct seq tuple
+        tuple_struct map struct identifier ignored_any
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn extracts_matches() {
+        let tickets = MaybeRegex::new("[A-Z]+-[0-9]+").as_case_sensitive();
+        let text = "Fixes ABC-12 and XY-3, see also ABC-12.";
+        assert_eq!(tickets.extract_all(text), vec!["ABC-12", "XY-3", "ABC-12"]);
+        assert_eq!(tickets.extract_unique(text), vec!["ABC-12", "XY-3"]);
+        assert_eq!(
+            MaybeRegex::new("abc").extract_all("ABC abc"),
+            vec!["ABC", "abc"]
+        );
+    }
+
+    #[test]
+    fn splits_on_matches() {
+        let separator = MaybeRegex::new(";\\s*");
+        assert_eq!(separator.split("a; b;c;"), vec!["a", "b", "c", ""]);
+        assert_eq!(separator.split_terminator("a; b;c;"), vec!["a", "b", "c"]);
+        assert_eq!(separator.split_terminator("a;b"), vec!["a", "b"]);
+        assert!(separator.split_terminator("").is_empty());
+        assert_eq!(MaybeRegex::new(",").split(",a"), vec!["", "a"]);
+    }
+
+    #[test]
+    fn extracts_named_groups() {
+        let pattern = MaybeRegex::new("(?P<key>\\w+)=(?P<value>\\d+)?(x)?");
+        assert_eq!(pattern.capture_names(), ["key", "value"]);
+        let fields = pattern.extract_named("a=b port=80");
+        assert_eq!(fields.len(), 1);
+        assert_eq!(fields["key"], "a");
+        assert_eq!(
+            pattern.extract_named("port=80"),
+            HashMap::from([("key".into(), "port".into()), ("value".into(), "80".into())])
+        );
+        assert!(pattern.extract_named("nothing").is_empty());
+        assert!(MaybeRegex::new("plain").capture_names().is_empty());
+        assert!(MaybeRegex::new("plain").extract_named("plain").is_empty());
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn extracts_into_structs() {
+        #[derive(serde::Deserialize, Debug, PartialEq)]
+        struct Connection {
+            host: String,
+            port: u16,
+            secure: Option<bool>,
+        }
+
+        let pattern =
+            MaybeRegex::new("(?P<host>[a-z.]+):(?P<port>[0-9]+)(?: tls=(?P<secure>[a-z]+))?");
+        let connections: Vec<Connection> = pattern
+            .extract_into("db.local:5432 tls=yes, cache:6379")
+            .unwrap();
+        assert_eq!(
+            connections,
+            vec![
+                Connection {
+                    host: "db.local".into(),
+                    port: 5432,
+                    secure: Some(true)
+                },
+                Connection {
+                    host: "cache".into(),
+                    port: 6379,
+                    secure: None
+                },
+            ]
+        );
+
+        let overflow: Result<Vec<Connection>, _> = pattern.extract_into("db:99999");
+        assert!(overflow.is_err());
+
+        #[derive(serde::Deserialize, Debug, PartialEq)]
+        struct Word {
+            word: String,
+        }
+        let whole = MaybeRegex::new("(?P<word>cat)").as_whole_word();
+        let words: Vec<Word> = whole.extract_into("concatenate cat").unwrap();
+        assert_eq!(words, [Word { word: "cat".into() }]);
+        let crlf = MaybeRegex::new("(?P<word>a\\nb)").as_line_ending_insensitive();
+        let words: Vec<Word> = crlf.extract_into("a\r\nb").unwrap();
+        assert_eq!(
+            words,
+            [Word {
+                word: "a\r\nb".into()
+            }]
+        );
+    }
+}
@@ -0,0 +1,213 @@
+use crate::{MaybeRegex, scratch::prepared_copy, utils::is_word_boundary};
+use std::ops::Range;
+
+/// A match found by `MaybeRegex::find` or `find_iter`, with offsets into the haystack as given,
+/// even when matching ignored case or line endings.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Match<'h> {
+    haystack: &'h str,
+    start: usize,
+    end: usize,
+}
+
+impl<'h> Match<'h> {
+    pub(crate) fn new(haystack: &'h str, range: Range<usize>) -> Self {
+        Self {
+            haystack,
+            start: range.start,
+            end: range.end,
+        }
+    }
+
+    pub fn start(&self) -> usize {
+        self.start
+    }
+
+    pub fn end(&self) -> usize {
+        self.end
+    }
+
+    pub fn range(&self) -> Range<usize> {
+        self.start..self.end
+    }
+
+    pub fn len(&self) -> usize {
+        self.end - self.start
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.start == self.end
+    }
+
+    /// The matched text.
+    pub fn as_str(&self) -> &'h str {
+        &self.haystack[self.range()]
+    }
+}
+
+impl MaybeRegex {
+    /// The first match in the haystack, ignoring negation, without collecting the rest like
+    /// `match_indices` does.
+    ///
+    /// ```
+    /// use maybe_regex::MaybeRegex;
+    ///
+    /// let found = MaybeRegex::new("disk").find("DISK full").unwrap();
+    /// assert_eq!((found.start(), found.end(), found.as_str()), (0, 4, "DISK"));
+    /// ```
+    pub fn find<'h>(&self, haystack: &'h str) -> Option<Match<'h>> {
+        self.with_matches(haystack, |matches| {
+            matches.next().map(|(start, len)| Match {
+                haystack,
+                start,
+                end: start + len,
+            })
+        })
+    }
+
+    /// Every match in the haystack, ignoring negation, found one at a time as the iterator is
+    /// advanced, so taking the first few hits of a large document doesn't scan all of it.
+    /// Matches don't overlap, like `match_indices`.
+    ///
+    /// A case-insensitive literal still folds the whole haystack up front.
+    ///
+    /// ```
+    /// use maybe_regex::MaybeRegex;
+    ///
+    /// let pattern = MaybeRegex::new("\\d+");
+    /// let numbers: Vec<_> = pattern.find_iter("1 22 333").take(2).map(|m| m.as_str()).collect();
+    /// assert_eq!(numbers, ["1", "22"]);
+    /// ```
+    pub fn find_iter<'h>(&self, haystack: &'h str) -> impl Iterator<Item = Match<'h>> {
+        self.search_iter(
+            haystack,
+            |text, from| Some((self.find_from(text, from)?, ())),
+            |found, (), to_original| Some(Match::new(haystack, to_original(found))),
+        )
+    }
+
+    // Finds matches one at a time, for `find_iter` and `captures_iter`. `search` gives the
+    // next match in the prepared haystack at or after an offset, with anything else found
+    // along with it, and `output` makes an item of it, using the given function to map
+    // ranges back to `haystack`.
+    pub(crate) fn search_iter<T, U>(
+        &self,
+        haystack: &str,
+        mut search: impl FnMut(&str, usize) -> Option<(Range<usize>, T)>,
+        mut output: impl FnMut(
+            Range<usize>,
+            T,
+            &mut dyn FnMut(Range<usize>) -> Range<usize>,
+        ) -> Option<U>,
+    ) -> impl Iterator<Item = U> {
+        #[cfg(feature = "normalization")]
+        let searched = self.normalize_haystack(haystack);
+        #[cfg(not(feature = "normalization"))]
+        let searched = std::borrow::Cow::Borrowed(haystack);
+        // Searched in place when neither normalizing nor preparing changes it
+        let prepared = match prepared_copy(&searched, self.folding(), self.normalize_line_endings) {
+            Some(prepared) => Some(prepared),
+            None => match searched {
+                std::borrow::Cow::Owned(normalized) => Some(normalized),
+                std::borrow::Cow::Borrowed(_) => None,
+            },
+        };
+        let mut map = None;
+        let mut from = 0;
+        let mut last_end = None;
+        std::iter::from_fn(move || {
+            let text = prepared.as_deref().unwrap_or(haystack);
+            if text.len() < self.min_len {
+                return None;
+            }
+            let mut to_original = |range: Range<usize>| {
+                if prepared.is_none() {
+                    return range;
+                }
+                let (start, len) = map
+                    .get_or_insert_with(|| self.offset_map(haystack))
+                    .to_original((range.start, range.len()));
+                start..start + len
+            };
+            while from <= text.len() {
+                let (found, extra) = search(text, from)?;
+                let next_char = text[found.start..].chars().next().map_or(1, char::len_utf8);
+                // Like the regex crate, an empty match right after the previous match is
+                // skipped
+                if (found.is_empty() && last_end == Some(found.start))
+                    || (self.whole_word
+                        && !(is_word_boundary(text, found.start, &self.word_characters)
+                            && is_word_boundary(text, found.end, &self.word_characters)))
+                {
+                    from = found.start + next_char;
+                    continue;
+                }
+                from = if found.is_empty() {
+                    found.end + next_char
+                } else {
+                    found.end
+                };
+                last_end = Some(found.end);
+                if let Some(item) = output(found, extra, &mut to_original) {
+                    return Some(item);
+                }
+            }
+            None
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn finds_first_match() {
+        let found = MaybeRegex::new("o\\w").find("hello world").unwrap();
+        assert_eq!((found.range(), found.as_str()), (7..9, "or"));
+        let found = MaybeRegex::new("stanbul").find("\u{130}STANBUL").unwrap();
+        assert_eq!((found.start(), found.as_str()), (2, "STANBUL"));
+        assert_eq!(MaybeRegex::new("x").find("abc"), None);
+        assert!(
+            MaybeRegex::new("")
+                .find("abc")
+                .is_some_and(|found| found.is_empty())
+        );
+        assert_eq!(
+            MaybeRegex::new("-b")
+                .find("abc")
+                .map(|found| found.as_str()),
+            Some("b")
+        );
+    }
+
+    #[test]
+    fn finds_matches_lazily() {
+        let found: Vec<_> = MaybeRegex::new("a*")
+            .find_iter("baaab")
+            .map(|found| found.range())
+            .collect();
+        assert_eq!(found, [0..0, 1..4, 5..5]);
+
+        let pattern = MaybeRegex::new("straße");
+        let mut found = pattern.find_iter("STRASSE, Straße");
+        assert_eq!(found.next().map(|found| found.as_str()), Some("STRASSE"));
+        assert_eq!(found.next().map(|found| found.range()), Some(9..16));
+        assert_eq!(found.next(), None);
+
+        let haystack = "cat concat cat";
+        let pattern = MaybeRegex::new("cat");
+        for pattern in [pattern.clone(), pattern.as_whole_word()] {
+            let expected: Vec<_> = pattern
+                .match_indices(haystack)
+                .into_iter()
+                .map(|(start, len)| start..start + len)
+                .collect();
+            let found: Vec<_> = pattern
+                .find_iter(haystack)
+                .map(|found| found.range())
+                .collect();
+            assert_eq!(found, expected);
+        }
+    }
+}
@@ -0,0 +1,62 @@
+use crate::MaybeRegex;
+use unicode_segmentation::UnicodeSegmentation;
+
+impl MaybeRegex {
+    /// Like `match_indices`, but as `(start, len)` in grapheme clusters rather than bytes, for
+    /// highlighting per character in a terminal or web page. A match covering part of a
+    /// cluster, like an emoji's skin tone, is widened to the whole cluster.
+    ///
+    /// ```
+    /// use maybe_regex::MaybeRegex;
+    ///
+    /// let pattern = MaybeRegex::new("cafe\u{301}");
+    /// assert_eq!(pattern.grapheme_match_indices("\u{1F44D}\u{1F3FD} cafe\u{301}!"), [(2, 4)]);
+    /// ```
+    pub fn grapheme_match_indices<S: AsRef<str>>(&self, haystack: S) -> Vec<(usize, usize)> {
+        let haystack = haystack.as_ref();
+        let indices = self.match_indices(haystack);
+        if indices.is_empty() {
+            return indices;
+        }
+
+        // Where each cluster starts, and where the last one ends
+        let boundaries: Vec<usize> = haystack
+            .grapheme_indices(true)
+            .map(|(index, _)| index)
+            .chain([haystack.len()])
+            .collect();
+        indices
+            .into_iter()
+            .map(|(start, len)| {
+                let first = boundaries.partition_point(|&b| b <= start) - 1;
+                let end = boundaries.partition_point(|&b| b < start + len);
+                (first, end.max(first) - first)
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn counts_grapheme_clusters() {
+        let thumbs = "\u{1F44D}\u{1F3FD}";
+        assert_eq!(
+            MaybeRegex::new("b").grapheme_match_indices(format!("a{thumbs}b b")),
+            [(2, 1), (4, 1)]
+        );
+        // Only the skin tone matches, so the whole emoji is reported
+        assert_eq!(
+            MaybeRegex::new("\u{1F3FD}").grapheme_match_indices(format!("x{thumbs}")),
+            [(1, 1)]
+        );
+        assert_eq!(
+            MaybeRegex::new("e\u{301}").grapheme_match_indices("re\u{301}sume\u{301}"),
+            [(1, 1), (5, 1)]
+        );
+        assert_eq!(MaybeRegex::new("$").grapheme_match_indices("ab"), [(2, 0)]);
+        assert_eq!(MaybeRegex::new("z").grapheme_match_indices("ab"), []);
+    }
+}
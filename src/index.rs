@@ -0,0 +1,182 @@
+use crate::MaybeRegexSet;
+use std::collections::HashMap;
+
+/// A document that matched a `SearchIndex` query.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SearchHit {
+    /// The id returned by `SearchIndex::add`.
+    pub document: usize,
+    pub score: f64,
+    /// Names of the fields a positive pattern was found in, in the order they were added.
+    pub fields: Vec<String>,
+}
+
+/// A small in-memory index of documents with named fields, ranked against a set of patterns.
+///
+/// A document's score is the sum, over each positive pattern and each field containing it, of
+/// the pattern's weight times the field's boost. A negative pattern found in any field
+/// excludes the document. Documents need at least one positive match unless the query has no
+/// positive patterns, in which case every document not excluded is returned with a score of 0.
+///
+/// ```
+/// use maybe_regex::SearchIndex;
+///
+/// let mut index = SearchIndex::new().with_boost("title", 3.0);
+/// let rust = index.add([("title", "Rust patterns"), ("body", "Matching text")]);
+/// let regex = index.add([("title", "Regex tips"), ("body", "Rust and regexes")]);
+///
+/// let hits = index.search_str("rust");
+/// assert_eq!(hits[0].document, rust);
+/// assert_eq!(hits[1].document, regex);
+/// assert!(index.search_str("rust -tips").iter().all(|hit| hit.document != regex));
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct SearchIndex {
+    fields: Vec<String>,
+    boosts: HashMap<String, f64>,
+    // Each document's (field id, text) pairs.
+    documents: Vec<Vec<(usize, String)>>,
+}
+
+impl SearchIndex {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Multiplies the score of matches in `field`. Fields default to 1.0.
+    pub fn with_boost<S: Into<String>>(mut self, field: S, boost: f64) -> Self {
+        self.set_boost(field, boost);
+        self
+    }
+
+    pub fn set_boost<S: Into<String>>(&mut self, field: S, boost: f64) {
+        self.boosts.insert(field.into(), boost);
+    }
+
+    pub fn boost(&self, field: &str) -> f64 {
+        self.boosts.get(field).copied().unwrap_or(1.0)
+    }
+
+    /// Adds a document and returns its id, which is its position in insertion order.
+    pub fn add<I, K, V>(&mut self, fields: I) -> usize
+    where
+        I: IntoIterator<Item = (K, V)>,
+        K: AsRef<str>,
+        V: Into<String>,
+    {
+        let document = fields
+            .into_iter()
+            .map(|(name, text)| (self.field_id(name.as_ref()), text.into()))
+            .collect();
+        self.documents.push(document);
+        self.documents.len() - 1
+    }
+
+    pub fn len(&self) -> usize {
+        self.documents.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.documents.is_empty()
+    }
+
+    /// Matching documents, best first. Ties keep insertion order.
+    pub fn search(&self, query: &MaybeRegexSet) -> Vec<SearchHit> {
+        let has_positive = query.iter().any(|pattern| !pattern.is_negative);
+        let mut hits: Vec<SearchHit> = self
+            .documents
+            .iter()
+            .enumerate()
+            .filter_map(|(document, fields)| {
+                let mut hit = SearchHit {
+                    document,
+                    score: 0.0,
+                    fields: vec![],
+                };
+                for (field, text) in fields {
+                    let name = &self.fields[*field];
+                    let mut matched = false;
+                    for (index, (contained, pattern)) in query
+                        .contained(text)
+                        .into_iter()
+                        .zip(query.iter())
+                        .enumerate()
+                    {
+                        match (contained, pattern.is_negative) {
+                            (true, true) => return None,
+                            (true, false) => {
+                                hit.score += query.weight(index).unwrap_or(1.0) * self.boost(name);
+                                matched = true;
+                            }
+                            _ => {}
+                        }
+                    }
+                    if matched && !hit.fields.contains(name) {
+                        hit.fields.push(name.clone());
+                    }
+                }
+                (!has_positive || !hit.fields.is_empty()).then_some(hit)
+            })
+            .collect();
+        hits.sort_by(|a, b| b.score.total_cmp(&a.score));
+        hits
+    }
+
+    /// Like `search`, treating each whitespace-separated word of `query` as a pattern.
+    pub fn search_str(&self, query: &str) -> Vec<SearchHit> {
+        self.search(&MaybeRegexSet::new(query.split_whitespace()))
+    }
+
+    fn field_id(&mut self, name: &str) -> usize {
+        match self.fields.iter().position(|field| field == name) {
+            Some(id) => id,
+            None => {
+                self.fields.push(name.to_string());
+                self.fields.len() - 1
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::MaybeRegex;
+
+    #[test]
+    fn ranks_by_weighted_fields() {
+        let mut index = SearchIndex::new()
+            .with_boost("title", 2.0)
+            .with_boost("tags", 0.5);
+        let a = index.add([("title", "Error handling"), ("body", "Use Result")]);
+        let b = index.add([
+            ("title", "Logging"),
+            ("body", "Log every error"),
+            ("tags", "error"),
+        ]);
+        let c = index.add([("title", "Errors everywhere"), ("tags", "draft")]);
+        let d = index.add([("body", "Nothing relevant")]);
+
+        let hits = index.search_str("error");
+        let order: Vec<_> = hits.iter().map(|hit| hit.document).collect();
+        assert_eq!(order, [a, c, b]);
+        assert_eq!(hits[2].score, 1.5);
+        assert_eq!(hits[2].fields, ["body", "tags"]);
+
+        let mut query = MaybeRegexSet::new(["-draft"]);
+        query.push_weighted(MaybeRegex::new("error"), 10.0);
+        let order: Vec<_> = index
+            .search(&query)
+            .iter()
+            .map(|hit| hit.document)
+            .collect();
+        assert_eq!(order, [a, b]);
+
+        let all: Vec<_> = index
+            .search_str("-draft")
+            .iter()
+            .map(|hit| hit.document)
+            .collect();
+        assert_eq!(all, [a, b, d]);
+    }
+}
@@ -0,0 +1,292 @@
+use crate::MaybeRegex;
+use serde_json::Value;
+use std::io::{self, BufRead, Write};
+
+/// A pattern applied to JSON records, either to the whole record or to one field.
+///
+/// Records are parsed before matching, so whole-record queries see a canonical compact
+/// rendering and field queries see the field's value regardless of key order or whitespace.
+#[derive(Debug, Clone, PartialEq)]
+pub struct JsonQuery {
+    field: Option<String>,
+    pattern: MaybeRegex,
+}
+
+impl JsonQuery {
+    pub fn new(pattern: MaybeRegex) -> Self {
+        Self {
+            field: None,
+            pattern,
+        }
+    }
+
+    /// Matches the value at `field`, a path like `request.method` (see `matches_json`).
+    pub fn field<S: Into<String>>(field: S, pattern: MaybeRegex) -> Self {
+        Self {
+            field: Some(field.into()),
+            pattern,
+        }
+    }
+
+    /// Parses `field:pattern`, or a bare pattern for the whole record. The part before the
+    /// first `:` is only taken as a field when it looks like a dotted path.
+    pub fn parse<S: AsRef<str>>(query: S) -> Self {
+        let query = query.as_ref();
+        match query.split_once(':') {
+            Some((field, pattern)) if is_field_path(field) => {
+                Self::field(field, MaybeRegex::new(pattern))
+            }
+            _ => Self::new(MaybeRegex::new(query)),
+        }
+    }
+
+    pub fn matches(&self, record: &Value) -> bool {
+        let contained = match &self.field {
+            None => self.pattern.is_contained_within(record.to_string()),
+            Some(field) => self.pattern.is_contained_within_json(record, field),
+        };
+        contained != self.pattern.is_negative
+    }
+
+    /// Copies the JSON Lines records from `reader` that match to `writer`, one per line and
+    /// unchanged. Blank lines are skipped; a line that isn't valid JSON is an
+    /// `InvalidData` error. Returns how many records were written.
+    pub fn filter_lines<R: BufRead, W: Write>(
+        &self,
+        reader: R,
+        mut writer: W,
+    ) -> io::Result<usize> {
+        let mut written = 0;
+        for (i, line) in reader.lines().enumerate() {
+            let line = line?;
+            if line.trim().is_empty() {
+                continue;
+            }
+            let record: Value = serde_json::from_str(&line).map_err(|e| {
+                io::Error::new(io::ErrorKind::InvalidData, format!("line {}: {e}", i + 1))
+            })?;
+            if self.matches(&record) {
+                writeln!(writer, "{line}")?;
+                written += 1;
+            }
+        }
+        Ok(written)
+    }
+}
+
+impl MaybeRegex {
+    /// Applies the pattern to the value(s) at `path` within `value`.
+    ///
+    /// Paths are dotted keys with optional brackets: `user.name`, `tags[0]`,
+    /// `headers["content-type"]`, and `*` or `[*]` for every element of an array or object,
+    /// as in `events[*].msg`. Strings are matched on their contents and other values on their
+    /// JSON text. When the path selects several values a positive pattern needs any of them
+    /// to match, while a negative one needs all of them to; a path that selects nothing (or
+    /// doesn't parse) only matches negative patterns.
+    pub fn matches_json(&self, value: &Value, path: &str) -> bool {
+        self.is_contained_within_json(value, path) != self.is_negative
+    }
+
+    fn is_contained_within_json(&self, value: &Value, path: &str) -> bool {
+        let Some(steps) = parse_path(path) else {
+            return false;
+        };
+        let mut values = vec![value];
+        for step in &steps {
+            values = values
+                .into_iter()
+                .flat_map(|value| step.select(value))
+                .collect();
+        }
+        values
+            .into_iter()
+            .any(|value| self.is_contained_within(text_of(value)))
+    }
+}
+
+#[derive(Debug, PartialEq)]
+enum Step {
+    Key(String),
+    Index(usize),
+    Wildcard,
+}
+
+impl Step {
+    fn select<'v>(&self, value: &'v Value) -> Vec<&'v Value> {
+        match (self, value) {
+            (Self::Wildcard, Value::Array(items)) => items.iter().collect(),
+            (Self::Wildcard, Value::Object(map)) => map.values().collect(),
+            (Self::Key(key), Value::Object(map)) => map.get(key).into_iter().collect(),
+            // Bare numeric keys index arrays too, e.g. `tags.0`
+            (Self::Key(key), Value::Array(items)) => key
+                .parse::<usize>()
+                .ok()
+                .and_then(|i| items.get(i))
+                .into_iter()
+                .collect(),
+            (Self::Index(i), Value::Array(items)) => items.get(*i).into_iter().collect(),
+            _ => vec![],
+        }
+    }
+}
+
+fn parse_path(path: &str) -> Option<Vec<Step>> {
+    let mut steps = vec![];
+    let mut chars = path.chars().peekable();
+    let mut key = String::new();
+    let mut after_bracket = false;
+    while let Some(c) = chars.next() {
+        match c {
+            '.' => {
+                if !after_bracket {
+                    steps.push(key_step(std::mem::take(&mut key))?);
+                }
+                after_bracket = false;
+            }
+            '[' => {
+                if !key.is_empty() {
+                    steps.push(key_step(std::mem::take(&mut key))?);
+                }
+                let mut inner = String::new();
+                match chars.peek() {
+                    Some(&quote @ ('"' | '\'')) => {
+                        chars.next();
+                        for c in chars.by_ref() {
+                            if c == quote {
+                                break;
+                            }
+                            inner.push(c);
+                        }
+                        if chars.next() != Some(']') {
+                            return None;
+                        }
+                        steps.push(Step::Key(inner));
+                    }
+                    _ => {
+                        for c in chars.by_ref() {
+                            if c == ']' {
+                                break;
+                            }
+                            inner.push(c);
+                        }
+                        steps.push(match inner.trim() {
+                            "*" => Step::Wildcard,
+                            index => Step::Index(index.parse().ok()?),
+                        });
+                    }
+                }
+                after_bracket = true;
+            }
+            // Only `.` or another `[` may follow a closing bracket
+            _ if after_bracket => return None,
+            c => key.push(c),
+        }
+    }
+    if after_bracket || (key.is_empty() && steps.is_empty()) {
+        return Some(steps);
+    }
+    steps.push(key_step(key)?);
+    Some(steps)
+}
+
+fn key_step(key: String) -> Option<Step> {
+    match key.as_str() {
+        "" => None,
+        "*" => Some(Step::Wildcard),
+        _ => Some(Step::Key(key)),
+    }
+}
+
+fn is_field_path(s: &str) -> bool {
+    !s.is_empty()
+        && !s.contains(char::is_whitespace)
+        && !s.starts_with(['"', '{', '['])
+        && parse_path(s).is_some()
+}
+
+// Strings match on their contents rather than their quoted JSON form.
+fn text_of(value: &Value) -> String {
+    match value {
+        Value::String(s) => s.clone(),
+        other => other.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const EVENTS: &str = r#"{"level":"error","msg":"disk full","ctx":{"host":"db-1"}}
+{ "msg": "slow query",   "level": "warn", "ctx": {"host": "db-2"} }
+
+{"level":"info","msg":"started","ctx":{"host":"web-1"}}
+"#;
+
+    fn filtered(query: &str) -> Vec<String> {
+        let mut output = vec![];
+        JsonQuery::parse(query)
+            .filter_lines(EVENTS.as_bytes(), &mut output)
+            .unwrap();
+        String::from_utf8(output)
+            .unwrap()
+            .lines()
+            .map(String::from)
+            .collect()
+    }
+
+    #[test]
+    fn filters_by_field_or_record() {
+        assert_eq!(filtered("ctx.host:^db-[0-9]").len(), 2);
+        assert_eq!(filtered("level:-info").len(), 2);
+        assert_eq!(
+            filtered("\"level\":\"warn\""),
+            vec![EVENTS.lines().nth(1).unwrap()]
+        );
+        assert_eq!(filtered("missing:x").len(), 0);
+        assert_eq!(filtered("disk").len(), 1);
+
+        let error = JsonQuery::parse("{not json")
+            .filter_lines("{oops\n".as_bytes(), io::sink())
+            .unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+    }
+
+    #[test]
+    fn matches_json_paths() {
+        let event: Value = serde_json::from_str(
+            r#"{
+                "user": {"name": "Alice", "roles": ["admin", "dev"]},
+                "headers": {"content-type": "application/json", "x.trace": "abc"},
+                "events": [{"msg": "disk full"}, {"msg": "slow query"}, {"code": 500}]
+            }"#,
+        )
+        .unwrap();
+
+        let matches =
+            |pattern: &str, path: &str| MaybeRegex::new(pattern).matches_json(&event, path);
+        assert!(matches("alice", "user.name"));
+        assert!(matches("^dev$", "user.roles[1]"));
+        assert!(matches("admin", "user.roles.0"));
+        assert!(matches("json", "headers[\"content-type\"]"));
+        assert!(matches("abc", "headers['x.trace']"));
+        assert!(matches("slow", "events[*].msg"));
+        assert!(matches("^500$", "events.*.code"));
+        assert!(matches("-timeout", "events[*].msg"));
+        assert!(!matches("-disk", "events[*].msg"));
+        assert!(!matches("alice", "user.missing"));
+        assert!(matches("-alice", "user.missing"));
+        assert!(!matches("alice", "user[name"));
+
+        assert_eq!(
+            parse_path("a[0][*].b"),
+            Some(vec![
+                Step::Key("a".into()),
+                Step::Index(0),
+                Step::Wildcard,
+                Step::Key("b".into())
+            ])
+        );
+        assert_eq!(parse_path("a..b"), None);
+        assert_eq!(parse_path(""), Some(vec![]));
+    }
+}
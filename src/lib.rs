@@ -1,13 +1,119 @@
-use crate::utils::{remove_first_n_chars, remove_last_n_chars};
+use crate::{
+    detect::{
+        SLASH_FLAGS, expand_quoted_spans, leading_case_insensitive, literal_text, looks_like_regex,
+        quoted_literal, slash_delimited,
+    },
+    scratch::{Folding, OffsetMap},
+    utils::{is_word_boundary, remove_first_n_chars, remove_last_n_chars, whole_word_matches},
+};
 use lazy_static::lazy_static;
 use log::error;
-use regex::{Captures, Regex, RegexBuilder, Replacer};
-use std::{cmp::Ordering, fmt::Display};
+use regex::{Regex, RegexBuilder};
+use std::{
+    borrow::Cow,
+    cell::OnceCell,
+    cmp::Ordering,
+    fmt::Display,
+    hash::{Hash, Hasher},
+    ops::Range,
+    str::FromStr,
+};
 
+mod args;
+mod automata;
+mod borrowed;
+mod builder;
+mod bytes;
+mod captures;
+mod classify;
+#[cfg(feature = "icu")]
+mod collation;
+mod combine;
+mod compile;
+mod complexity;
+#[cfg(feature = "csv")]
+mod csv;
+mod detect;
+mod diacritics;
+mod encoding;
+mod env;
+mod error;
+mod explain;
+mod extract;
+mod find;
+#[cfg(feature = "graphemes")]
+mod graphemes;
+mod index;
+#[cfg(feature = "json")]
+mod json;
+mod like;
+mod lint;
+mod literals;
+mod matrix;
+#[cfg(feature = "normalization")]
+mod normalize;
+mod options;
+mod path;
+mod profile;
+#[cfg(feature = "random")]
+mod random;
+mod redact;
+mod rules;
+mod scratch;
+mod search;
+mod set;
+mod set_builder;
+mod simplify;
+mod snippet;
+mod spans;
+mod tokens;
 mod utils;
+#[cfg(feature = "ignore")]
+mod walk;
+#[cfg(feature = "watch")]
+mod watch;
+mod whitespace;
+
+pub use automata::OverlapResult;
+pub use borrowed::MaybeRegexRef;
+pub use builder::{Detection, FallbackPolicy, MaybeRegexBuilder, NegationMarkers, RegexDetector};
+pub use captures::Captures;
+pub use classify::{Confidence, DetectionReason, DetectionReport};
+#[cfg(feature = "icu")]
+pub use collation::Collation;
+pub use combine::{matches_all, matches_any};
+pub use compile::{CompileReport, DEFAULT_MEMORY_BUDGET};
+pub use complexity::{Complexity, EXPENSIVE_COST_PER_BYTE};
+#[cfg(feature = "csv")]
+pub use csv::{CsvColumn, CsvFilter};
+pub use encoding::Encoding;
+pub use error::{MaybeRegexError, SetBuildError, ValidationError};
+pub use explain::Explanation;
+pub use find::Match;
+pub use index::{SearchHit, SearchIndex};
+#[cfg(feature = "json")]
+pub use json::JsonQuery;
+pub use lint::LintWarning;
+pub use matrix::{BitMatrix, match_matrix};
+#[cfg(feature = "normalization")]
+pub use normalize::Normalization;
+pub use options::{Anchor, MatchOptions};
+pub use path::PathMatchMode;
+pub use profile::{Histogram, PatternProfile, ProfiledSet};
+pub use rules::{EvaluationOrder, Rule, RuleAction, RuleList};
+pub use scratch::Scratch;
+pub use search::{CancellationToken, LineMatch, Progress, SearchResults, Searcher};
+pub use set::MaybeRegexSet;
+pub use set_builder::{MaybeRegexSetBuilder, PatternOptions};
+pub use snippet::Snippet;
+pub use spans::SpanOptions;
+pub use tokens::{PatternToken, TokenKind};
+#[cfg(feature = "watch")]
+pub use watch::WatchedPatternSet;
 
 lazy_static! {
-    // Simplistic check to see if a string is likely a regex.
+    // Characters that are rarely used outside of regexes, for telling broken regexes apart
+    // from plain text.
     static ref REGEX_REGEX: Regex = Regex::new(
         r"(?i)[\$\^\[\]\+\*\.]|\\[dwsbB]{1}").unwrap();
 }
@@ -17,27 +123,163 @@ pub struct MaybeRegex {
     data: TagWrapperData,
     original: String,
     pub is_negative: bool,
+    // Whether the input was negated with a trailing `-` rather than a leading one.
+    negated_by_suffix: bool,
     case_sensitive: bool,
+    path_mode: Option<PathMatchMode>,
+    min_len: usize,
+    // The lowercased plain-text needle that case-insensitive matching searches the lowercased
+    // haystack for, when it differs from the needle itself.
+    folded: Option<String>,
+    // Whether case-insensitive plain text only folds ASCII letters, see
+    // `MaybeRegexBuilder::ascii`.
+    ascii: bool,
+    // Whether matches need a word boundary on either side, checked around each hit rather than
+    // with `\b` in the regex: always for plain text, and for regexes with `word_characters`.
+    // See `as_whole_word`.
+    whole_word: bool,
+    // Characters counted as part of words besides letters, digits and `_`, from
+    // `MaybeRegexBuilder::word_characters`.
+    word_characters: String,
+    #[cfg(feature = "normalization")]
+    normalization: Option<Normalization>,
+    normalize_line_endings: bool,
+    // Whether the regex was built by the caller, with options its source doesn't show, so
+    // sets match it with its own `Regex` rather than compiling its source. See `from_regex`.
+    prebuilt: bool,
+    variants: options::VariantCache,
 }
 
+// Patterns are compared by what the user asked for, namely the text, negation and case
+// sensitivity, rather than how it was compiled.
 impl PartialEq for MaybeRegex {
     fn eq(&self, other: &Self) -> bool {
-        self.original == other.original && self.is_negative == other.is_negative
+        self.key() == other.key()
+    }
+}
+
+impl Eq for MaybeRegex {}
+
+// Compares against the input the pattern was created from, as shown by `Display`, so
+// `MaybeRegex::new("-foo") == "-foo"` and `MaybeRegex::new("--v") == "--v"`.
+impl PartialEq<str> for MaybeRegex {
+    fn eq(&self, other: &str) -> bool {
+        let (prefix, escape, suffix) = self.markers();
+        let mut other = Some(other);
+        for present in [prefix, escape] {
+            if present {
+                other = other.and_then(|other| other.strip_prefix('-'));
+            }
+        }
+        if suffix {
+            other = other.and_then(|other| other.strip_suffix('-'));
+        }
+        other == Some(self.to_str())
+    }
+}
+
+impl PartialEq<&str> for MaybeRegex {
+    fn eq(&self, other: &&str) -> bool {
+        self == *other
+    }
+}
+
+impl PartialEq<MaybeRegex> for str {
+    fn eq(&self, other: &MaybeRegex) -> bool {
+        other == self
+    }
+}
+
+impl PartialEq<MaybeRegex> for &str {
+    fn eq(&self, other: &MaybeRegex) -> bool {
+        other == *self
+    }
+}
+
+impl Hash for MaybeRegex {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.key().hash(state);
     }
 }
 
 impl PartialOrd for MaybeRegex {
     fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
-        (&self.original, self.is_negative).partial_cmp(&(&other.original, other.is_negative))
+        Some(self.cmp(other))
+    }
+}
+
+// By pattern text, then positive before negative, then case-insensitive before sensitive.
+impl Ord for MaybeRegex {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.key().cmp(&other.key())
     }
 }
 
+/// How a pattern is matched. More kinds may be added, so matches on it need a wildcard arm.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum PatternKind {
+    /// A plain-text needle.
+    Literal,
+    Regex,
+}
+
+/// Whether a pattern keeps the haystacks it's found in or the ones it isn't. Same as
+/// `is_negative`, for code that would rather not juggle booleans.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Polarity {
+    #[default]
+    Include,
+    /// A negative pattern, like `-foo`.
+    Exclude,
+}
+
+/// Why a haystack did or didn't match, for UIs that explain why an item was filtered out.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum MatchOutcome {
+    Matched,
+    /// A positive pattern wasn't found.
+    NotMatched,
+    /// A negative pattern was found.
+    ExcludedByNegative,
+}
+
+impl MatchOutcome {
+    pub fn is_match(self) -> bool {
+        self == Self::Matched
+    }
+}
+
+/// How specific a pattern is, ordered from most to least specific.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Specificity {
+    /// A plain-text needle.
+    Literal,
+    /// A regex anchored to the start of the haystack, e.g. `^foo.*`.
+    Prefix,
+    /// Any other regex.
+    Regex,
+}
+
 #[derive(Debug, Clone)]
 pub enum TagWrapperData {
     Raw(String),
     Regex(Regex),
 }
 
+/// Everything a `MaybeRegex` is made of, from `MaybeRegex::into_parts`.
+#[derive(Debug, Clone)]
+pub struct MaybeRegexParts {
+    /// The compiled regex, or the needle searched for. This can differ from `original`, e.g.
+    /// after `as_line_ending_insensitive`.
+    pub data: TagWrapperData,
+    /// The pattern as given, without the negation marker.
+    pub original: String,
+    pub is_negative: bool,
+    pub case_sensitive: bool,
+    pub normalize_line_endings: bool,
+}
+
 impl Default for TagWrapperData {
     fn default() -> Self {
         Self::Raw(String::default())
@@ -49,37 +291,297 @@ impl MaybeRegex {
         Self::from(s)
     }
 
+    // `/pattern/flags` input is always a regex, with its flags (`i`, `m`, `s`, `x` and `U`)
+    // set as in `RegexBuilder`. Like in vim and JavaScript, it's case sensitive without `i`.
     pub fn from<S: AsRef<str>>(s: S) -> Self {
-        let s = s.as_ref();
-        let (s, is_negative) = if s.starts_with("-") {
-            (remove_first_n_chars(s, 1), true)
-        } else if s.ends_with("-") {
-            (remove_last_n_chars(s, 1), true)
+        let (s, is_negative, negated_by_suffix) = split_negation(s.as_ref());
+        if let Some(Ok(regex)) = slash_regex(&s) {
+            return Self::with_data(
+                TagWrapperData::Regex(regex),
+                s,
+                is_negative,
+                negated_by_suffix,
+            )
+            .with_regex_case();
+        }
+        let expanded = expand_quoted_spans(&s);
+        let data = match plain_text(&expanded) {
+            Some(text) => TagWrapperData::Raw(text.into_owned()),
+            None => match get_regex(&expanded) {
+                Some(regex) => TagWrapperData::Regex(regex),
+                None => TagWrapperData::Raw(s.clone()),
+            },
+        };
+        Self::with_data(data, s, is_negative, negated_by_suffix).with_inline_case()
+    }
+
+    // Like `new`, but input that looks like a regex and doesn't compile is an error rather
+    // than being logged and matched as plain text.
+    pub fn try_new<S: AsRef<str>>(s: S) -> Result<Self, MaybeRegexError> {
+        let (s, is_negative, negated_by_suffix) = split_negation(s.as_ref());
+        match slash_regex(&s) {
+            Some(Ok(regex)) => {
+                let data = TagWrapperData::Regex(regex);
+                return Ok(
+                    Self::with_data(data, s, is_negative, negated_by_suffix).with_regex_case()
+                );
+            }
+            Some(Err(error)) => return Err(MaybeRegexError::InvalidRegex { pattern: s, error }),
+            None => {}
+        }
+        let expanded = expand_quoted_spans(&s);
+        let data = if let Some(text) = plain_text(&expanded) {
+            TagWrapperData::Raw(text.into_owned())
+        } else if looks_like_regex(&expanded) {
+            match RegexBuilder::new(&expanded).case_insensitive(true).build() {
+                Ok(regex) => TagWrapperData::Regex(regex),
+                Err(error) => return Err(MaybeRegexError::InvalidRegex { pattern: s, error }),
+            }
         } else {
-            (s.into(), false)
+            TagWrapperData::Raw(s.clone())
         };
+        Ok(Self::with_data(data, s, is_negative, negated_by_suffix).with_inline_case())
+    }
 
-        match get_regex(&s) {
-            Some(regex) => Self {
-                data: TagWrapperData::Regex(regex),
-                original: s,
-                is_negative,
-                case_sensitive: false,
-            },
-            None => Self {
-                data: TagWrapperData::Raw(s.clone()),
-                original: s,
-                is_negative,
-                case_sensitive: false,
-            },
+    // Matches every haystack, for use as "no filter". Same as `MaybeRegex::default()` and
+    // `MaybeRegex::new("")`.
+    pub fn match_all() -> Self {
+        Self::default()
+    }
+
+    // Matches no haystack. It's the negation of `match_all`, and displays as `-`.
+    pub fn match_none() -> Self {
+        Self {
+            is_negative: true,
+            ..Self::default()
+        }
+    }
+
+    // Always a plain substring, skipping the regex heuristic. A leading or trailing `-` is part
+    // of the text; set `is_negative` for a negative pattern.
+    pub fn literal<S: Into<String>>(s: S) -> Self {
+        let s = s.into();
+        Self::with_data(TagWrapperData::Raw(s.clone()), s, false, false)
+    }
+
+    // Always a regex, skipping the heuristic, so e.g. `a|b` is an alternation. Like `literal`,
+    // `-` has no special meaning.
+    pub fn regex<S: Into<String>>(s: S) -> Result<Self, MaybeRegexError> {
+        let s = s.into();
+        match RegexBuilder::new(&s).case_insensitive(true).build() {
+            Ok(regex) => Ok(
+                Self::with_data(TagWrapperData::Regex(regex), s, false, false).with_inline_case(),
+            ),
+            Err(error) => Err(MaybeRegexError::InvalidRegex { pattern: s, error }),
+        }
+    }
+
+    // Wraps a regex that was already built, keeping whatever options it was built with. The
+    // haystack is passed through unchanged, as if `as_case_sensitive`, so a case-sensitive
+    // regex stays case sensitive. A `MaybeRegexSet` matches it on its own rather than
+    // compiling it with the other patterns, since its options aren't part of its source.
+    pub fn from_regex(regex: Regex) -> Self {
+        let original = regex.as_str().to_string();
+        Self::from_regex_with_original(regex, original)
+    }
+
+    // Like `from_regex`, with `original` as the text shown by `to_str` and `Display`, e.g. what
+    // the user typed before it was turned into a regex.
+    pub fn from_regex_with_original<S: Into<String>>(regex: Regex, original: S) -> Self {
+        let mut pattern =
+            Self::with_data(TagWrapperData::Regex(regex), original.into(), false, false);
+        pattern.prebuilt = true;
+        // Options like `ignore_whitespace` change what the source means, so its length says
+        // nothing about the shortest match
+        pattern.min_len = 0;
+        pattern.with_regex_case()
+    }
+
+    fn key(&self) -> (&str, bool, bool) {
+        (&self.original, self.is_negative, self.case_sensitive)
+    }
+
+    fn with_data(
+        data: TagWrapperData,
+        original: String,
+        is_negative: bool,
+        negated_by_suffix: bool,
+    ) -> Self {
+        let mut pattern = Self {
+            min_len: min_len_of(&data, true),
+            data,
+            original,
+            is_negative,
+            negated_by_suffix,
+            ..Default::default()
+        };
+        pattern.refold();
+        pattern
+    }
+
+    // Matches any of the given exact strings. They're escaped into a single alternation, which
+    // the regex engine searches for with Aho-Corasick rather than trying each in turn. An empty
+    // list matches nothing.
+    pub fn any_of<I, S>(literals: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        let alternation = literals
+            .into_iter()
+            .map(|literal| regex::escape(literal.as_ref()))
+            .collect::<Vec<_>>()
+            .join("|");
+        let s = if alternation.is_empty() {
+            r"[^\s\S]".to_string()
+        } else {
+            format!("(?:{alternation})")
+        };
+
+        let data = match build_regex(&s, true) {
+            Some(regex) => TagWrapperData::Regex(regex),
+            None => TagWrapperData::Raw(s.clone()),
+        };
+        Self::with_data(data, s, false, false)
+    }
+
+    // Regexes starting with inline case flags, like `(?i)` or `(?-i)`, handle case themselves,
+    // so the haystack is passed through unchanged rather than lowercased, which would break
+    // e.g. `(?-i)[A-Z]`. They stay case insensitive with `(?i)` even after `as_case_sensitive`.
+    pub(crate) fn with_inline_case(self) -> Self {
+        if let TagWrapperData::Regex(regex) = &self.data
+            && leading_case_insensitive(regex.as_str()).is_some()
+        {
+            return self.with_regex_case();
         }
+        self
+    }
+
+    // Leaves case to the regex as it was compiled, e.g. by `from_regex`, rather than to this
+    // pattern's case sensitivity.
+    pub(crate) fn with_regex_case(mut self) -> Self {
+        self.case_sensitive = true;
+        self.refold();
+        self
     }
 
+    // Regexes are compiled case-insensitive unless this is called, so it rebuilds them from
+    // their source. Builder options that aren't inline flags like `(?m)` are lost.
     pub fn as_case_sensitive(mut self) -> Self {
+        if !self.case_sensitive
+            && let TagWrapperData::Regex(regex) = &self.data
+            && let Some(rebuilt) = build_regex(regex.as_str(), false)
+        {
+            self.data = TagWrapperData::Regex(rebuilt);
+        }
         self.case_sensitive = true;
+        self.refold();
+        self
+    }
+
+    // Only matches with a word boundary on either side, e.g. `cat` no longer matches `concat`.
+    // Plain text stays plain text, checking the characters around each hit rather than going
+    // through the regex engine.
+    pub fn as_whole_word(mut self) -> Self {
+        if self.is_regex() && self.word_characters.is_empty() {
+            return self.wrapped(|pattern| format!(r"\b(?:{pattern})\b"));
+        }
+        self.whole_word = true;
+        self
+    }
+
+    // Makes `matches` and friends require the pattern to span the whole haystack rather than
+    // appear anywhere in it, for literals and regexes alike.
+    pub fn as_full_match(self) -> Self {
+        self.wrapped(|pattern| format!(r"\A(?:{pattern})\z"))
+    }
+
+    // Replaces the pattern with a regex built around it. Literals are escaped first.
+    fn wrapped(mut self, wrap: impl FnOnce(&str) -> String) -> Self {
+        let pattern = wrap(&self.regex_source());
+        if let Some(regex) = build_regex(&pattern, !self.case_sensitive) {
+            self.data = TagWrapperData::Regex(regex);
+            self.whole_word = false;
+            self.min_len = min_len_of(&self.data, true);
+        }
+        self
+    }
+
+    // The pattern as regex syntax: a regex's source, or the needle escaped, between `\b`s
+    // when it's whole-word. Those don't know about `word_characters`.
+    pub(crate) fn regex_source(&self) -> Cow<'_, str> {
+        let source = match &self.data {
+            TagWrapperData::Raw(value) => Cow::Owned(regex::escape(value)),
+            TagWrapperData::Regex(regex) => Cow::Borrowed(regex.as_str()),
+        };
+        if self.whole_word {
+            Cow::Owned(format!(r"\b(?:{source})\b"))
+        } else {
+            source
+        }
+    }
+
+    // The hits in the prepared haystack with a word boundary on either side.
+    fn whole_word_matches<'a>(
+        &'a self,
+        haystack: &'a str,
+    ) -> impl Iterator<Item = Range<usize>> + 'a {
+        whole_word_matches(haystack, &self.word_characters, move |from| {
+            self.find_from(haystack, from)
+        })
+    }
+
+    // The first match in the prepared haystack starting at or after `from`, ignoring
+    // `whole_word`.
+    pub(crate) fn find_from(&self, haystack: &str, from: usize) -> Option<Range<usize>> {
+        match &self.data {
+            TagWrapperData::Raw(value) => {
+                let needle = self.needle(value);
+                let start = from + haystack[from..].find(needle)?;
+                Some(start..start + needle.len())
+            }
+            TagWrapperData::Regex(regex) => {
+                regex.find_at(haystack, from).map(|found| found.range())
+            }
+        }
+    }
+
+    // Treats CRLF line endings in the haystack as LF, so `$` in multi-line regexes, ends_with,
+    // and needles containing `\n` behave the same on Windows-originated text.
+    pub fn as_line_ending_insensitive(mut self) -> Self {
+        self.normalize_line_endings = true;
+        if let TagWrapperData::Raw(value) = &mut self.data {
+            *value = value.replace("\r\n", "\n");
+            self.refold();
+        }
+        self
+    }
+
+    // Flips between matching haystacks that contain the pattern and ones that don't.
+    pub fn negate(mut self) -> Self {
+        self.is_negative = !self.is_negative;
         self
     }
 
+    pub fn polarity(&self) -> Polarity {
+        if self.is_negative {
+            Polarity::Exclude
+        } else {
+            Polarity::Include
+        }
+    }
+
+    pub fn set_polarity(&mut self, polarity: Polarity) {
+        self.is_negative = polarity == Polarity::Exclude;
+    }
+
+    pub fn kind(&self) -> PatternKind {
+        match &self.data {
+            TagWrapperData::Raw(_) => PatternKind::Literal,
+            TagWrapperData::Regex(_) => PatternKind::Regex,
+        }
+    }
+
     pub fn is_regex(&self) -> bool {
         match &self.data {
             TagWrapperData::Raw(_) => false,
@@ -87,6 +589,58 @@ impl MaybeRegex {
         }
     }
 
+    // The compiled regex, to reuse elsewhere. Note it's built case-insensitive and expects a
+    // lowercased haystack unless the pattern is case sensitive.
+    pub fn as_regex(&self) -> Option<&Regex> {
+        match &self.data {
+            TagWrapperData::Raw(_) => None,
+            TagWrapperData::Regex(regex) => Some(regex),
+        }
+    }
+
+    // The needle searched for when the pattern isn't a regex.
+    pub fn as_literal(&self) -> Option<&str> {
+        match &self.data {
+            TagWrapperData::Raw(value) => Some(value),
+            TagWrapperData::Regex(_) => None,
+        }
+    }
+
+    pub fn into_parts(self) -> MaybeRegexParts {
+        MaybeRegexParts {
+            data: self.data,
+            original: self.original,
+            is_negative: self.is_negative,
+            case_sensitive: self.case_sensitive,
+            normalize_line_endings: self.normalize_line_endings,
+        }
+    }
+
+    pub fn specificity(&self) -> Specificity {
+        match &self.data {
+            TagWrapperData::Raw(_) => Specificity::Literal,
+            TagWrapperData::Regex(_) if self.original.starts_with('^') => Specificity::Prefix,
+            TagWrapperData::Regex(_) => Specificity::Regex,
+        }
+    }
+
+    // Orders more specific patterns first: literals before prefix patterns before
+    // general regexes, and longer patterns before shorter ones within each group.
+    // Useful for sorting rule lists into a sensible evaluation or display order.
+    pub fn specificity_cmp(&self, other: &Self) -> Ordering {
+        self.specificity()
+            .cmp(&other.specificity())
+            .then_with(|| other.original.len().cmp(&self.original.len()))
+            .then_with(|| self.original.cmp(&other.original))
+            .then_with(|| self.is_negative.cmp(&other.is_negative))
+    }
+
+    // The fewest bytes any match can span. Haystacks shorter than this are rejected without
+    // running the matcher, and external indexes can use it to skip short candidates.
+    pub fn min_match_len(&self) -> usize {
+        self.min_len
+    }
+
     pub fn matches<S: AsRef<str>>(&self, haystack: S) -> bool {
         let matches = self.is_contained_within(haystack);
         if self.is_negative {
@@ -95,135 +649,441 @@ impl MaybeRegex {
         matches
     }
 
+    // Like `matches`, telling apart a positive pattern that wasn't found from a negative one
+    // that was.
+    pub fn match_outcome<S: AsRef<str>>(&self, haystack: S) -> MatchOutcome {
+        match (self.is_contained_within(haystack), self.is_negative) {
+            (true, true) => MatchOutcome::ExcludedByNegative,
+            (false, false) => MatchOutcome::NotMatched,
+            _ => MatchOutcome::Matched,
+        }
+    }
+
+    pub fn validate<S: AsRef<str>>(&self, input: S) -> Result<(), ValidationError> {
+        let input = input.as_ref();
+        if self.is_negative {
+            match self.spans(input).into_iter().next() {
+                Some(span) => Err(ValidationError::Blocked {
+                    pattern: self.original.clone(),
+                    span,
+                }),
+                None if self.is_contained_within(input) => Err(ValidationError::Blocked {
+                    pattern: self.original.clone(),
+                    span: 0..0,
+                }),
+                None => Ok(()),
+            }
+        } else if self.is_contained_within(input) {
+            Ok(())
+        } else {
+            Err(ValidationError::MissingRequired {
+                pattern: self.original.clone(),
+            })
+        }
+    }
+
     // You likely want matches, which considers whether the input is "negative" or not.
     // This ignores that and just returns whether the needle is found inside the haystack.
     pub fn is_contained_within<S: AsRef<str>>(&self, haystack: S) -> bool {
-        let haystack = if self.case_sensitive {
-            haystack.as_ref()
-        } else {
-            &haystack.as_ref().to_lowercase()
-        };
+        // The empty pattern is contained everywhere; no need to prepare the haystack
+        if matches!(&self.data, TagWrapperData::Raw(value) if value.is_empty() && !self.whole_word)
+        {
+            return true;
+        }
+        self.with_prepared(haystack.as_ref(), |haystack| {
+            self.is_contained_within_prepared(haystack)
+        })
+    }
 
+    pub(crate) fn is_contained_within_prepared(&self, haystack: &str) -> bool {
+        if haystack.len() < self.min_len {
+            return false;
+        }
+
+        if self.whole_word {
+            return self.whole_word_matches(haystack).next().is_some();
+        }
         match &self.data {
-            TagWrapperData::Raw(value) => haystack.contains(value),
+            TagWrapperData::Raw(value) => haystack.contains(self.needle(value)),
             TagWrapperData::Regex(regex) => regex.is_match(haystack),
         }
     }
 
     pub fn replace(&self, str: String, to_string: impl Fn(&str) -> String + 'static) -> String {
-        let mut output = str;
-        match &self.data {
-            TagWrapperData::Raw(value) => {
-                let replacement = to_string(value);
-                output = output.replace(value, &replacement);
-            }
-            TagWrapperData::Regex(regex) => {
-                let highlighter = Highlighter {
-                    to_string_cb: Box::new(to_string),
-                };
-
-                // TODO: Silly hack since replace_all doesn't seem to span multiple lines
-                output = output.replace("\n", "abcdefg");
-                output = regex.replace_all(&output, highlighter).to_string();
-                output = output.replace("abcdefg", "\n");
-            }
-        };
-        output
+        self.replace_spans(&str, to_string)
     }
 
     pub fn to_str(&self) -> &str {
         self.original.as_str()
     }
 
+    // Byte offsets into `other` as `(start, len)`, even where matching saw a transformed copy,
+    // e.g. lowercased or with CRLF line endings normalized.
     pub fn match_indices<S: AsRef<str>>(&self, other: S) -> Vec<(usize, usize)> {
-        let other = if self.case_sensitive {
-            other.as_ref()
-        } else {
-            &other.as_ref().to_lowercase()
-        };
+        self.with_matches(other.as_ref(), |matches| matches.collect())
+    }
 
-        match &self.data {
-            TagWrapperData::Raw(value) => other
-                .match_indices(value)
-                .map(|(index, _)| (index, value.len()))
-                .collect(),
-            TagWrapperData::Regex(regex) => regex
-                .find_iter(other)
-                .map(|some_match| (some_match.start(), some_match.len()))
-                .collect(),
+    // Runs `f` on the matches in `haystack` as `(start, len)`, mapped back from the prepared
+    // haystack when it differs.
+    pub(crate) fn with_matches<R>(
+        &self,
+        haystack: &str,
+        f: impl FnOnce(&mut dyn Iterator<Item = (usize, usize)>) -> R,
+    ) -> R {
+        let original = haystack;
+        #[cfg(feature = "normalization")]
+        let haystack = &*self.normalize_haystack(haystack);
+        let (folding, normalize_line_endings) = (self.folding(), self.normalize_line_endings);
+        scratch::with_prepared(haystack, folding, normalize_line_endings, |prepared| {
+            let mut found: Box<dyn Iterator<Item = (usize, usize)>> = match &self.data {
+                _ if prepared.len() < self.min_len => Box::new(std::iter::empty()),
+                _ if self.whole_word => Box::new(
+                    self.whole_word_matches(prepared)
+                        .map(|found| (found.start, found.len())),
+                ),
+                TagWrapperData::Raw(value) => {
+                    let needle = self.needle(value);
+                    Box::new(
+                        prepared
+                            .match_indices(needle)
+                            .map(|(index, _)| (index, needle.len())),
+                    )
+                }
+                TagWrapperData::Regex(regex) => Box::new(
+                    regex
+                        .find_iter(prepared)
+                        .map(|found| (found.start(), found.len())),
+                ),
+            };
+            if std::ptr::eq(prepared, original) {
+                return f(&mut found);
+            }
+            let map = OnceCell::new();
+            f(&mut found.map(|span| {
+                map.get_or_init(|| self.offset_map(original))
+                    .to_original(span)
+            }))
+        })
+    }
+
+    // Whether there are at least `n` matches, stopping as soon as the `n`th is found. Like
+    // match_indices, matches don't overlap and negation is ignored.
+    pub fn matches_at_least<S: AsRef<str>>(&self, haystack: S, n: usize) -> bool {
+        if n == 0 {
+            return true;
         }
+        self.with_prepared(haystack.as_ref(), |haystack| {
+            if haystack.len() < self.min_len.saturating_mul(n) {
+                return false;
+            }
+
+            match &self.data {
+                _ if self.whole_word => self.whole_word_matches(haystack).nth(n - 1).is_some(),
+                TagWrapperData::Raw(value) => {
+                    haystack.matches(self.needle(value)).nth(n - 1).is_some()
+                }
+                TagWrapperData::Regex(regex) => regex.find_iter(haystack).nth(n - 1).is_some(),
+            }
+        })
     }
 
-    pub fn matches_exactly<S: AsRef<str>>(&self, other: S) -> bool {
-        let other = if self.case_sensitive {
-            other.as_ref()
-        } else {
-            &other.as_ref().to_lowercase()
-        };
+    // The number of matches, like `match_indices(..).len()` without collecting their
+    // positions. Like match_indices, matches don't overlap and negation is ignored.
+    pub fn count<S: AsRef<str>>(&self, haystack: S) -> usize {
+        self.with_prepared(haystack.as_ref(), |haystack| {
+            if haystack.len() < self.min_len {
+                return 0;
+            }
 
-        match &self.data {
-            TagWrapperData::Raw(value) => other == *value,
+            match &self.data {
+                _ if self.whole_word => self.whole_word_matches(haystack).count(),
+                TagWrapperData::Raw(value) => haystack.matches(self.needle(value)).count(),
+                TagWrapperData::Regex(regex) => regex.find_iter(haystack).count(),
+            }
+        })
+    }
+
+    // Like match_indices, but as byte ranges guaranteed to be valid slices of `haystack`.
+    pub(crate) fn spans(&self, haystack: &str) -> Vec<Range<usize>> {
+        self.match_indices(haystack)
+            .into_iter()
+            .map(|(start, len)| start..start + len)
+            .collect()
+    }
+
+    // Rebuilds `haystack` with every match replaced by `replacement(matched_text)`.
+    pub(crate) fn replace_spans(
+        &self,
+        haystack: &str,
+        mut replacement: impl FnMut(&str) -> String,
+    ) -> String {
+        let mut output = String::with_capacity(haystack.len());
+        let mut last = 0;
+        for range in self.spans(haystack) {
+            if range.start < last {
+                continue;
+            }
+            output.push_str(&haystack[last..range.start]);
+            output.push_str(&replacement(&haystack[range.clone()]));
+            last = range.end;
+        }
+        output.push_str(&haystack[last..]);
+        output
+    }
+
+    pub fn matches_exactly<S: AsRef<str>>(&self, other: S) -> bool {
+        self.with_prepared(other.as_ref(), |other| match &self.data {
+            _ if self.whole_word => self.whole_word_matches(other).next() == Some(0..other.len()),
+            TagWrapperData::Raw(value) => other == self.needle(value),
             TagWrapperData::Regex(regex) => {
                 if let Some(found) = regex.find(other) {
                     return found.len() == other.len();
                 }
                 false
             }
-        }
+        })
     }
 
     pub fn starts_with<S: AsRef<str>>(&self, s: S) -> bool {
-        let s = if self.case_sensitive {
-            s.as_ref()
-        } else {
-            &s.as_ref().to_lowercase()
-        };
-
-        match &self.data {
-            TagWrapperData::Raw(value) => value.starts_with(s),
+        self.with_prepared(s.as_ref(), |s| match &self.data {
+            TagWrapperData::Raw(value) => self.needle(value).starts_with(s),
             TagWrapperData::Regex(regex) => {
                 if let Some(found) = regex.find(s) {
                     return found.start() == 0;
                 }
                 false
             }
+        })
+    }
+
+    // Whether the haystack ends with a match. For regexes this compiles an end-anchored copy
+    // of the pattern once, cached like the variants of `matches_with`.
+    pub fn ends_with<S: AsRef<str>>(&self, haystack: S) -> bool {
+        self.with_prepared(haystack.as_ref(), |haystack| {
+            if haystack.len() < self.min_len {
+                return false;
+            }
+
+            match &self.data {
+                TagWrapperData::Raw(value) => {
+                    let needle = self.needle(value);
+                    let start = haystack.len() - needle.len().min(haystack.len());
+                    haystack.ends_with(needle)
+                        && (!self.whole_word
+                            || is_word_boundary(haystack, start, &self.word_characters)
+                                && is_word_boundary(
+                                    haystack,
+                                    haystack.len(),
+                                    &self.word_characters,
+                                ))
+                }
+                TagWrapperData::Regex(_) if self.whole_word => self
+                    .whole_word_matches(haystack)
+                    .last()
+                    .is_some_and(|found| found.end == haystack.len()),
+                TagWrapperData::Regex(regex) => {
+                    let flags = if self.case_sensitive { "" } else { "(?i)" };
+                    self.variants
+                        .get(format!("{flags}(?:{})\\z", regex.as_str()))
+                        .is_some_and(|anchored| anchored.is_match(haystack))
+                }
+            }
+        })
+    }
+
+    // Keeps `folded` and `min_len` in step with a plain-text needle and its case sensitivity.
+    fn refold(&mut self) {
+        self.folded = None;
+        if let TagWrapperData::Raw(value) = &self.data {
+            let folded = match self.folding() {
+                Folding::None => Cow::Borrowed(value.as_str()),
+                Folding::Ascii => scratch::fold_ascii(value),
+                Folding::Unicode => scratch::fold(value),
+            };
+            if let Cow::Owned(folded) = folded {
+                self.folded = Some(folded);
+            }
+            // A case-insensitive match may be shorter than the needle, e.g. for the Kelvin sign
+            self.min_len = value.len().min(self.needle(value).len());
+        }
+    }
+
+    // What a plain-text needle is searched for as in the prepared haystack.
+    pub(crate) fn needle<'a>(&'a self, value: &'a str) -> &'a str {
+        self.folded.as_deref().unwrap_or(value)
+    }
+
+    // Whether the pattern is written with a leading negation marker, a `-` escaping a leading
+    // `-` in the text, and a trailing negation marker. Text starting with `-` can only be
+    // negated with the trailing marker.
+    pub(crate) fn markers(&self) -> (bool, bool, bool) {
+        let escape = self.original.starts_with('-');
+        let suffix = self.is_negative && (self.negated_by_suffix || escape);
+        (self.is_negative && !suffix, escape, suffix)
+    }
+
+    // Runs `f` on the haystack as the needle sees it: normalized like the pattern, lowercased
+    // for case-insensitive plain text, and with CRLF line endings turned into LF when they're
+    // normalized. Regexes are compiled case-insensitive instead, since lowercasing breaks e.g.
+    // `\p{Lu}` and `(?-i)`.
+    fn with_prepared<R>(&self, haystack: &str, f: impl FnOnce(&str) -> R) -> R {
+        #[cfg(feature = "normalization")]
+        let haystack = &*self.normalize_haystack(haystack);
+        scratch::with_prepared(haystack, self.folding(), self.normalize_line_endings, f)
+    }
+
+    // Maps offsets in `haystack` once prepared, including any normalization, back into it.
+    pub(crate) fn offset_map(&self, haystack: &str) -> OffsetMap {
+        #[cfg(feature = "normalization")]
+        if let Some(form) = self.normalization {
+            return OffsetMap::new(
+                form.sources(haystack),
+                haystack.len(),
+                self.folding(),
+                self.normalize_line_endings,
+            );
+        }
+        let chars = haystack
+            .char_indices()
+            .map(|(index, c)| (c, index..index + c.len_utf8()));
+        OffsetMap::new(
+            chars,
+            haystack.len(),
+            self.folding(),
+            self.normalize_line_endings,
+        )
+    }
+
+    // How the haystack's case is folded before searching it.
+    pub(crate) fn folding(&self) -> Folding {
+        if self.case_sensitive || self.is_regex() {
+            Folding::None
+        } else if self.ascii {
+            Folding::Ascii
+        } else {
+            Folding::Unicode
         }
     }
 }
 
+// The pattern as the user wrote it, including the negation marker on whichever side it was
+// on, so it parses back to an equal pattern. `to_str` is the pattern without the marker.
 impl Display for MaybeRegex {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "{}", self.to_str())
+        let (prefix, escape, suffix) = self.markers();
+        for (marker, present) in [("-", prefix), ("-", escape)] {
+            if present {
+                f.write_str(marker)?;
+            }
+        }
+        f.write_str(self.to_str())?;
+        if suffix {
+            f.write_str("-")?;
+        }
+        Ok(())
     }
 }
 
-fn get_regex(s: &str) -> Option<Regex> {
-    if REGEX_REGEX.is_match(s) {
-        match RegexBuilder::new(s).case_insensitive(true).build() {
-            Ok(regex) => {
-                return Some(regex);
-            }
-            Err(_e) => {
-                error!("Bad regex: {s}");
-            }
+// Parses like `try_new`, so invalid regexes are reported rather than matched as plain text.
+impl FromStr for MaybeRegex {
+    type Err = MaybeRegexError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::try_new(s)
+    }
+}
+
+impl From<Regex> for MaybeRegex {
+    fn from(regex: Regex) -> Self {
+        Self::from_regex(regex)
+    }
+}
+
+// Strips a leading or trailing `-`, returning the rest, whether there was one, and whether it
+// was trailing. A leading `--` stands for a `-` in the text, e.g. `--v` is the text `-v`, which
+// can still be negated with a trailing `-`.
+fn split_negation(s: &str) -> (String, bool, bool) {
+    if s.starts_with("--") {
+        let text = remove_first_n_chars(s, 1);
+        if text.len() > 1 && text.ends_with("-") {
+            (remove_last_n_chars(&text, 1), true, true)
+        } else {
+            (text, false, false)
         }
+    } else if s.starts_with("-") {
+        (remove_first_n_chars(s, 1), true, false)
+    } else if s.ends_with("-") {
+        (remove_last_n_chars(s, 1), true, true)
+    } else {
+        (s.into(), false, false)
+    }
+}
+
+// Input that's searched for as plain text even though it may look like a regex: quoted with
+// a leading `=` or double quotes, like `=1+1` or `"1+1"`, or made only of literals, like
+// `foo\.bar`, which is much faster without the regex engine.
+// Shared with `MaybeRegexRef`, which borrows quoted text.
+pub(crate) fn plain_text(s: &str) -> Option<Cow<'_, str>> {
+    if let Some(text) = quoted_literal(s) {
+        return Some(Cow::Borrowed(text));
+    }
+    if !looks_like_regex(s) {
+        return None;
+    }
+    literal_text(s).map(Cow::Owned)
+}
+
+// Compiles `/pattern/flags` input, case sensitive unless it has the `i` flag. Returns `None`
+// for other input.
+// The flags become inline flags, so they survive the pattern being rebuilt from its source.
+fn slash_regex(s: &str) -> Option<Result<Regex, regex::Error>> {
+    let (pattern, flags) = slash_delimited(s)?;
+    let flags: String = SLASH_FLAGS
+        .chars()
+        .filter(|&flag| flag != 'g' && flags.contains(flag))
+        .collect();
+    if flags.is_empty() {
+        Some(Regex::new(pattern))
+    } else {
+        Some(Regex::new(&format!("(?{flags}){pattern}")))
+    }
+}
+
+fn get_regex(s: &str) -> Option<Regex> {
+    if looks_like_regex(s) {
+        return build_regex(s, true);
     }
     None
 }
 
-struct Highlighter {
-    to_string_cb: Box<dyn Fn(&str) -> String>,
+fn min_len_of(data: &TagWrapperData, case_insensitive: bool) -> usize {
+    match data {
+        TagWrapperData::Raw(value) => value.len(),
+        TagWrapperData::Regex(regex) => regex_syntax::ParserBuilder::new()
+            .case_insensitive(case_insensitive)
+            .build()
+            .parse(regex.as_str())
+            .ok()
+            .and_then(|hir| hir.properties().minimum_len())
+            .unwrap_or(0),
+    }
 }
 
-impl Replacer for Highlighter {
-    fn replace_append(&mut self, caps: &Captures<'_>, dst: &mut String) {
-        let temp = caps.get(0).map_or("", |m| m.as_str()).to_string();
-        let rv = (*self.to_string_cb)(&temp);
-        dst.push_str(&rv);
+fn build_regex(s: &str, case_insensitive: bool) -> Option<Regex> {
+    match RegexBuilder::new(s)
+        .case_insensitive(case_insensitive)
+        .build()
+    {
+        Ok(regex) => Some(regex),
+        Err(_e) => {
+            error!("Bad regex: {s}");
+            None
+        }
     }
 }
 
 #[cfg(test)]
+#[allow(clippy::needless_borrows_for_generic_args, clippy::cmp_owned)]
 mod test {
     use super::*;
 
@@ -247,6 +1107,239 @@ mod test {
         assert!(!MaybeRegex::new("This is not a regex b").is_regex());
     }
 
+    #[test]
+    fn treats_repeated_repetitions_as_text() {
+        assert!(!MaybeRegex::new("C++").is_regex());
+    }
+
+    #[test]
+    fn searches_escaped_literals_as_plain_text() {
+        let pattern = MaybeRegex::new("-example\\.com");
+        assert!(!pattern.is_regex() && pattern.is_negative);
+        assert_eq!(pattern.as_literal(), Some("example.com"));
+        assert_eq!(pattern.to_string(), "-example\\.com");
+        assert!(pattern.is_contained_within("www.EXAMPLE.com"));
+        assert!(!pattern.is_contained_within("exampleXcom"));
+        assert!(MaybeRegex::try_new("a\\+b").unwrap().as_literal() == Some("a+b"));
+    }
+
+    #[test]
+    fn quoting_forces_literals() {
+        for input in ["=1+1", "\"1+1\""] {
+            let pattern = MaybeRegex::new(input);
+            assert_eq!(pattern.as_literal(), Some("1+1"));
+            assert!(pattern.matches("1+1=2") && !pattern.matches("11=2"));
+            assert_eq!(pattern.to_string(), input);
+        }
+        assert_eq!(MaybeRegex::new("-=a.b").as_literal(), Some("a.b"));
+        assert!(MaybeRegex::new("-=a.b").is_negative);
+        assert!(!MaybeRegex::try_new("=[unclosed").unwrap().is_regex());
+        assert_eq!(MaybeRegex::new("\"").as_literal(), Some("\""));
+    }
+
+    #[test]
+    fn parses_slash_delimited_flags() {
+        let sensitive = MaybeRegex::new("/Error/");
+        assert!(sensitive.is_regex());
+        assert!(sensitive.matches("Error: x") && !sensitive.matches("error: x"));
+        assert!(MaybeRegex::new("/Error/i").matches("error: x"));
+
+        let lines = "first\nsecond";
+        assert!(!MaybeRegex::new("/^second$/").matches(lines));
+        assert!(MaybeRegex::new("/^second$/m").matches(lines));
+        assert!(MaybeRegex::new("/first.second/s").matches(lines));
+
+        let negative = MaybeRegex::new("-/debug/gi");
+        assert!(negative.is_negative && !negative.matches("DEBUG"));
+        assert_eq!(negative.to_string(), "-/debug/gi");
+
+        assert!(MaybeRegex::try_new("/[bad/i").is_err());
+        assert!(!MaybeRegex::new("/usr/bin").is_regex());
+    }
+
+    #[test]
+    fn honors_quoted_spans() {
+        let file = MaybeRegex::new("\\Qfile[1].txt\\E");
+        assert_eq!(file.as_literal(), Some("file[1].txt"));
+        assert_eq!(file.to_string(), "\\Qfile[1].txt\\E");
+
+        let version = MaybeRegex::try_new("^\\Qv1.2\\E\\d$").unwrap();
+        assert!(version.is_regex());
+        assert!(version.matches("v1.23") && !version.matches("v1x23"));
+    }
+
+    #[test]
+    fn respects_inline_case_flags() {
+        let upper = MaybeRegex::new("(?-i)^[A-Z]+$");
+        assert!(upper.matches("ERROR") && !upper.matches("error"));
+        let insensitive = MaybeRegex::new("(?i)error");
+        assert!(insensitive.matches("ERROR") && insensitive.matches("error"));
+        assert!(
+            MaybeRegex::new("(?i)\\p{Lu}")
+                .as_case_sensitive()
+                .matches("Ab")
+        );
+        assert_eq!(
+            MaybeRegex::regex("(?-i)A").unwrap().matches("a"),
+            MaybeRegex::new("(?-i)A").matches("a")
+        );
+    }
+
+    #[test]
+    fn escapes_leading_dashes() {
+        let flag = MaybeRegex::new("--v");
+        assert!(!flag.is_negative && flag.matches("ls -v") && !flag.matches("v"));
+        assert_eq!((flag.to_str(), flag.to_string().as_str()), ("-v", "--v"));
+
+        let excluded = MaybeRegex::new("--v-");
+        assert!(excluded.is_negative && !excluded.matches("ls -v"));
+        assert_eq!(excluded.to_str(), "-v");
+
+        let mut negated = MaybeRegex::new("--v");
+        negated.is_negative = true;
+        assert_eq!(negated.to_string(), "--v-");
+        assert_eq!(MaybeRegex::new("--").to_str(), "-");
+        for input in ["--v", "--v-", "--", "---", "-v", "v-"] {
+            assert_eq!(MaybeRegex::new(input).to_string(), input);
+        }
+    }
+
+    #[test]
+    fn changes_polarity() {
+        let pattern = MaybeRegex::new("draft");
+        assert_eq!(pattern.polarity(), Polarity::Include);
+        let mut negated = pattern.clone().negate();
+        assert_eq!(negated.polarity(), Polarity::Exclude);
+        assert!(!negated.matches("draft 2") && negated.matches("final"));
+        assert_eq!(negated.to_string(), "-draft");
+        assert_eq!(negated.clone().negate(), pattern);
+
+        negated.set_polarity(Polarity::Include);
+        assert_eq!(negated, pattern);
+        negated.set_polarity(Polarity::Exclude);
+        assert!(negated.is_negative);
+    }
+
+    #[test]
+    fn reports_match_outcome() {
+        let positive = MaybeRegex::new("error");
+        assert_eq!(positive.match_outcome("an error"), MatchOutcome::Matched);
+        assert_eq!(positive.match_outcome("fine"), MatchOutcome::NotMatched);
+        let negative = MaybeRegex::new("-debug");
+        assert_eq!(negative.match_outcome("info"), MatchOutcome::Matched);
+        assert_eq!(
+            negative.match_outcome("debug: x"),
+            MatchOutcome::ExcludedByNegative
+        );
+        for haystack in ["an error", "fine", "debug: x"] {
+            for pattern in [&positive, &negative] {
+                assert_eq!(
+                    pattern.match_outcome(haystack).is_match(),
+                    pattern.matches(haystack)
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn compiles_regexes_with_case_sensitivity() {
+        let upper = MaybeRegex::new("^[A-Z]+$").as_case_sensitive();
+        assert!(upper.matches("ABC") && !upper.matches("abc"));
+        assert!(MaybeRegex::new("^[A-Z]+$").matches("abc"));
+        assert!(MaybeRegex::new("ERROR \\d").matches("Error 4"));
+
+        // The haystack isn't lowercased, so offsets are into the text as given
+        assert_eq!(MaybeRegex::new("x$").match_indices("İx"), [(2, 1)]);
+        let set = MaybeRegexSet::new(["^[A-Z]+$", "-x"]);
+        assert!(set.matches("ABC") && set.matches("abc") && !set.matches("abx"));
+    }
+
+    #[test]
+    fn lowercases_literal_needles() {
+        let hello = MaybeRegex::new("Hello");
+        assert!(hello.matches("hello world") && hello.matches("HELLO"));
+        assert_eq!(hello.as_literal(), Some("Hello"));
+        assert_eq!(hello.match_indices("say HELLO"), [(4, 5)]);
+        assert!(hello.clone().as_case_sensitive().matches("Hello"));
+        assert!(!hello.as_case_sensitive().matches("hello"));
+
+        assert!(MaybeRegex::literal("C++").matches("c++ code"));
+        assert!(MaybeRegex::new("Foo\\.Bar").matches("foo.bar"));
+        assert!(MaybeRegex::new("Straße").ends_with("STRASSE straße"));
+        assert!(MaybeRegexSet::new(["Error", "-Debug"]).matches("error: x"));
+    }
+
+    #[test]
+    fn reports_offsets_into_the_original_haystack() {
+        let haystack = "\u{130}STANBUL \u{130}stanbul";
+        let indices = MaybeRegex::new("stanbul").match_indices(haystack);
+        assert_eq!(indices, [(2, 7), (12, 7)]);
+        assert_eq!(&haystack[2..9], "STANBUL");
+        assert_eq!(
+            MaybeRegex::new("k").match_indices("\u{212A}x k"),
+            [(0, 3), (5, 1)]
+        );
+        assert_eq!(MaybeRegex::new("ss").match_indices("Straße"), [(4, 2)]);
+        assert_eq!(
+            MaybeRegex::new("Ü\nb")
+                .as_line_ending_insensitive()
+                .match_indices("xü\r\nB"),
+            [(1, 5)]
+        );
+    }
+
+    #[test]
+    fn matches_whole_word_literals_without_regex() {
+        let cat = MaybeRegex::new("cat").as_whole_word();
+        assert!(!cat.is_regex() && cat.matches("a CAT.") && !cat.matches("concatenate"));
+        assert_eq!(cat.match_indices("cat concat Cat"), [(0, 3), (11, 3)]);
+        assert!(cat.matches_at_least("cat, cat", 2) && !cat.matches_at_least("cat cats", 2));
+        assert!(cat.matches_exactly("Cat") && !cat.is_contained_within_bytes(b"cats"));
+        assert!(cat.ends_with("a cat") && !cat.ends_with("bobcat"));
+        assert_eq!(
+            MaybeRegex::new("ab ab")
+                .as_whole_word()
+                .match_indices("xab ab ab"),
+            [(4, 5)]
+        );
+        assert!(!cat.clone().as_full_match().matches("cat!"));
+
+        let set = MaybeRegexSet::builder()
+            .whole_word(true)
+            .pattern("cat")
+            .pattern("-dog")
+            .build()
+            .unwrap();
+        assert!(set.compile_report().compiled);
+        assert!(set.matches("my cat") && set.matches("cat hotdog"));
+        assert!(!set.matches("concat") && !set.matches("cat dog"));
+    }
+
+    #[test]
+    fn case_folds_unicode() {
+        assert!(MaybeRegex::new("straße").matches("STRASSE"));
+        assert!(MaybeRegex::new("STRASSE").matches("straße"));
+        assert!(MaybeRegex::new("ΟΔΟΣ").matches("οδος"));
+        assert!(MaybeRegex::new("οδος").matches("ΟΔΟΣ"));
+        assert!(MaybeRegex::new("\u{212A}elvin").matches("kelvin"));
+        assert!(MaybeRegex::new("\u{212A}\\w+").matches("KELVIN"));
+        assert!(MaybeRegex::new("ſ").matches("S"));
+        assert!(
+            !MaybeRegex::new("straße")
+                .as_case_sensitive()
+                .matches("STRASSE")
+        );
+    }
+
+    #[test]
+    fn detects_alternation_groups_braces_and_optionals() {
+        assert!(MaybeRegex::new("foo|bar").matches("a bar"));
+        assert!(MaybeRegex::new("(ab)+c").matches("ababc"));
+        assert!(MaybeRegex::new("x{2,3}").matches("axxb"));
+        assert!(!MaybeRegex::new("x{2,3}").matches("axb"));
+        assert!(MaybeRegex::new("colou?r").matches("color"));
+    }
+
     #[test]
     fn contains_works() {
         assert!(!MaybeRegex::new("z").is_contained_within("Hello"));
@@ -283,4 +1376,319 @@ mod test {
     fn implements_to_string() {
         assert!(MaybeRegex::new("howdy").to_string() == String::from("howdy"));
     }
+
+    #[test]
+    fn sorts_by_specificity() {
+        let mut patterns = [
+            MaybeRegex::new("o$"),
+            MaybeRegex::new("^He"),
+            MaybeRegex::new("ell"),
+            MaybeRegex::new("Hello"),
+        ];
+        patterns.sort_by(|a, b| a.specificity_cmp(b));
+        let sorted: Vec<&str> = patterns.iter().map(|p| p.to_str()).collect();
+        assert_eq!(sorted, vec!["Hello", "ell", "^He", "o$"]);
+    }
+
+    #[test]
+    fn computes_min_match_len() {
+        assert_eq!(MaybeRegex::new("hello").min_match_len(), 5);
+        assert_eq!(MaybeRegex::new("^ab+c?$").min_match_len(), 2);
+        assert_eq!(MaybeRegex::new(".*").min_match_len(), 0);
+        assert!(!MaybeRegex::new("ab+c").is_contained_within("ab"));
+        assert!(MaybeRegex::new("-ab+c").matches("ab"));
+    }
+
+    #[test]
+    fn validate_reports_reasons() {
+        assert_eq!(MaybeRegex::new("@").validate("me@example.com"), Ok(()));
+        assert_eq!(
+            MaybeRegex::new("@").validate("me"),
+            Err(ValidationError::MissingRequired {
+                pattern: "@".into()
+            })
+        );
+        assert_eq!(
+            MaybeRegex::new("-[0-9]").validate("abc1d"),
+            Err(ValidationError::Blocked {
+                pattern: "[0-9]".into(),
+                span: 3..4
+            })
+        );
+        assert_eq!(MaybeRegex::new("-[0-9]").validate("abcd"), Ok(()));
+    }
+
+    #[test]
+    fn try_new_rejects_invalid_regexes() {
+        let pattern = MaybeRegex::try_new("-^err(or)?$").unwrap();
+        assert!(pattern.is_regex() && pattern.is_negative);
+        assert_eq!(
+            MaybeRegex::try_new("plain text").unwrap().to_str(),
+            "plain text"
+        );
+
+        let Err(MaybeRegexError::InvalidRegex { pattern, .. }) = MaybeRegex::try_new("[unclosed")
+        else {
+            panic!("expected an invalid regex");
+        };
+        assert_eq!(pattern, "[unclosed");
+        assert!(!MaybeRegex::new("[unclosed").is_regex());
+    }
+
+    #[test]
+    // The interior mutability clippy sees is the variant cache, which isn't hashed
+    #[allow(clippy::mutable_key_type)]
+    fn hashes_consistently_with_eq() {
+        use std::collections::HashSet;
+        let patterns: HashSet<_> = ["foo", "-foo", "foo", "foo-"]
+            .into_iter()
+            .map(MaybeRegex::new)
+            .chain([MaybeRegex::new("foo").as_case_sensitive()])
+            .collect();
+        assert_eq!(patterns.len(), 3);
+        assert_ne!(
+            MaybeRegex::new("foo"),
+            MaybeRegex::new("foo").as_case_sensitive()
+        );
+    }
+
+    #[test]
+    fn orders_totally() {
+        let mut patterns = vec![
+            MaybeRegex::new("b"),
+            MaybeRegex::new("-a"),
+            MaybeRegex::new("a").as_case_sensitive(),
+            MaybeRegex::new("a"),
+            MaybeRegex::new("a"),
+        ];
+        patterns.sort();
+        patterns.dedup();
+        let ordered: Vec<_> = patterns
+            .iter()
+            .map(|pattern| (pattern.to_string(), pattern.case_sensitive))
+            .collect();
+        assert_eq!(
+            ordered,
+            [
+                ("a".to_string(), false),
+                ("a".to_string(), true),
+                ("-a".to_string(), false),
+                ("b".to_string(), false),
+            ]
+        );
+    }
+
+    #[test]
+    fn sentinels_match_everything_or_nothing() {
+        for haystack in ["", "anything", "ÉCOLE\r\n"] {
+            assert!(MaybeRegex::match_all().matches(haystack));
+            assert!(MaybeRegex::default().matches(haystack));
+            assert!(!MaybeRegex::match_none().matches(haystack));
+        }
+        assert_eq!(MaybeRegex::new("-"), MaybeRegex::match_none());
+        let mut set = MaybeRegexSet::new(["foo"]);
+        set.push(MaybeRegex::match_none());
+        assert!(!set.matches("foo"));
+    }
+
+    #[test]
+    fn replaces_what_matching_finds() {
+        let replace = |pattern: MaybeRegex, haystack: &str| {
+            pattern.replace(haystack.to_string(), |found| format!("[{found}]"))
+        };
+        let word = MaybeRegex::new("hello").as_whole_word();
+        assert_eq!(replace(word, "hello othello"), "[hello] othello");
+        assert_eq!(replace(MaybeRegex::new("disk"), "a DISK"), "a [DISK]");
+        let crlf = MaybeRegex::new("a\nb").as_line_ending_insensitive();
+        assert_eq!(replace(crlf, "a\r\nb"), "[a\r\nb]");
+        assert_eq!(replace(MaybeRegex::new("a.c"), "abc\nadc"), "[abc]\n[adc]");
+    }
+
+    #[test]
+    fn compares_with_input_strings() {
+        assert_eq!(MaybeRegex::new("foo.*"), "foo.*");
+        assert_eq!(MaybeRegex::new("-foo"), "-foo");
+        assert_eq!("foo-", MaybeRegex::new("foo-"));
+        assert_ne!(MaybeRegex::new("-foo"), "foo");
+        assert_ne!(MaybeRegex::new("foo-"), "-foo");
+        assert!(*"bar" == MaybeRegex::new("bar"));
+        assert_eq!(MaybeRegex::new("--v"), "--v");
+        assert_eq!(MaybeRegex::new("--v-"), "--v-");
+        assert_ne!(MaybeRegex::new("--v"), "-v");
+    }
+
+    #[test]
+    fn reports_kind() {
+        assert_eq!(MaybeRegex::new("hello").kind(), PatternKind::Literal);
+        assert_eq!(MaybeRegex::new("-^hello").kind(), PatternKind::Regex);
+    }
+
+    #[test]
+    fn exposes_parts() {
+        let regex = MaybeRegex::new("-^a+$");
+        assert_eq!(regex.as_regex().map(Regex::as_str), Some("^a+$"));
+        assert_eq!(regex.as_literal(), None);
+        let literal = MaybeRegex::new("a\r\nb").as_line_ending_insensitive();
+        assert_eq!(literal.as_literal(), Some("a\nb"));
+
+        let parts = regex.into_parts();
+        assert!(matches!(parts.data, TagWrapperData::Regex(_)));
+        assert_eq!(parts.original, "^a+$");
+        assert!(parts.is_negative && !parts.case_sensitive);
+    }
+
+    #[test]
+    fn display_round_trips() {
+        for input in ["foo", "-foo.*", "bar-", "^a|b$", "-"] {
+            let pattern = MaybeRegex::new(input);
+            assert_eq!(pattern.to_string(), input);
+            assert_eq!(MaybeRegex::new(pattern.to_string()), pattern);
+        }
+        let mut toggled = MaybeRegex::new("foo");
+        toggled.is_negative = true;
+        assert_eq!(toggled.to_string(), "-foo");
+    }
+
+    #[test]
+    fn parses_from_str() {
+        let pattern: MaybeRegex = "-foo.*".parse().unwrap();
+        assert!(pattern.is_negative && pattern.is_regex());
+        assert!("(foo[".parse::<MaybeRegex>().is_err());
+    }
+
+    #[test]
+    fn explicit_constructors_skip_heuristic() {
+        let literal = MaybeRegex::literal("-a.b");
+        assert!(!literal.is_regex() && !literal.is_negative);
+        assert!(literal.matches("x-a.b"));
+        assert!(!literal.matches("-axb"));
+
+        let regex = MaybeRegex::regex("hot dogs?").unwrap();
+        assert!(regex.is_regex());
+        assert!(regex.matches("a hot dog"));
+        assert!(!MaybeRegex::new("hot dogs?").matches("a hot dog"));
+        assert!(matches!(
+            MaybeRegex::regex("(open"),
+            Err(MaybeRegexError::InvalidRegex { .. })
+        ));
+    }
+
+    #[test]
+    fn wraps_prebuilt_regexes() {
+        let regex = RegexBuilder::new("^HELLO").build().unwrap();
+        let pattern: MaybeRegex = regex.into();
+        assert!(pattern.matches("HELLO world"));
+        assert!(!pattern.matches("hello world"));
+        assert_eq!(pattern.to_str(), "^HELLO");
+
+        let insensitive = RegexBuilder::new("w.rld")
+            .case_insensitive(true)
+            .build()
+            .unwrap();
+        let pattern = MaybeRegex::from_regex_with_original(insensitive, "w?rld");
+        assert!(pattern.matches("Hello WORLD"));
+        assert_eq!(pattern.to_string(), "w?rld");
+    }
+
+    #[test]
+    fn ignores_the_source_length_of_prebuilt_regexes() {
+        let spaced = RegexBuilder::new("a b c")
+            .ignore_whitespace(true)
+            .build()
+            .unwrap();
+        let pattern = MaybeRegex::from_regex(spaced);
+        assert_eq!(pattern.min_match_len(), 0);
+        assert!(pattern.matches("abc"));
+    }
+
+    #[test]
+    fn matches_prebuilt_regexes_on_their_own_in_sets() {
+        let insensitive = RegexBuilder::new("error")
+            .case_insensitive(true)
+            .build()
+            .unwrap();
+        let set: MaybeRegexSet = [
+            MaybeRegex::from_regex(insensitive),
+            MaybeRegex::new("-debug"),
+        ]
+        .into_iter()
+        .collect();
+        assert!(set.compile_report().compiled);
+        assert!(set.matches("ERROR: disk") && !set.matches("ERROR: debug"));
+        assert_eq!(set.matching_indices("ERROR"), [0]);
+    }
+
+    #[test]
+    fn full_match_applies_to_literals_and_regexes() {
+        let literal = MaybeRegex::new("to do").as_full_match();
+        assert!(literal.matches("To Do"));
+        assert!(!literal.matches("to do list"));
+        assert!(!literal.matches("not to do"));
+
+        let regex = MaybeRegex::new("[0-9]+").as_full_match();
+        assert!(regex.matches("123"));
+        assert!(!regex.matches("123a"));
+        assert!(regex.matches_bytes(b"42"));
+        assert!(MaybeRegex::new("-[0-9]+").as_full_match().matches("12x"));
+    }
+
+    #[test]
+    fn normalizes_line_endings() {
+        let crlf = "first line\r\nsecond line\r\n";
+        let lf = "first line\nsecond line\n";
+
+        let line_end = MaybeRegex::new("(?m)line$").as_line_ending_insensitive();
+        assert_eq!(line_end.match_indices(lf).len(), 2);
+        assert_eq!(line_end.match_indices(crlf), vec![(6, 4), (19, 4)]);
+        assert!(!MaybeRegex::new("(?m)line$").matches("first line\r\nx"));
+
+        let literal = MaybeRegex::new("line\nsecond").as_line_ending_insensitive();
+        assert!(literal.matches(crlf) && literal.matches(lf));
+        assert_eq!(literal.match_indices(crlf), vec![(6, 12)]);
+
+        let ends = MaybeRegex::new("line\n").as_line_ending_insensitive();
+        assert!(ends.ends_with(crlf) && ends.ends_with(lf));
+        assert!(MaybeRegex::new("[a-z]+\\.rs").ends_with("src/lib.rs"));
+        assert!(!MaybeRegex::new("[a-z]+\\.rs").ends_with("src/lib.rs.bak"));
+    }
+
+    #[test]
+    fn matches_any_of_literals() {
+        let any = MaybeRegex::any_of(["a.b", "c+", "Foo"]);
+        assert!(any.is_regex());
+        assert!(any.matches("xx A.B yy"));
+        assert!(any.matches("c+"));
+        assert!(any.matches("foo"));
+        assert!(!any.matches("axb"));
+        assert!(!any.matches("cc"));
+        assert_eq!(any.match_indices("c+ foo"), vec![(0, 2), (3, 3)]);
+        assert_eq!(any.min_match_len(), 2);
+        assert!(!MaybeRegex::any_of(Vec::<&str>::new()).matches("anything"));
+    }
+
+    #[test]
+    fn counts_matches_with_early_exit() {
+        let todo = MaybeRegex::new("todo");
+        assert!(todo.matches_at_least("TODO: a, todo: b, ToDo: c", 3));
+        assert!(!todo.matches_at_least("TODO: a, todo: b", 3));
+        assert!(todo.matches_at_least("", 0));
+        assert!(!MaybeRegex::new("aa").matches_at_least("aaa", 2));
+        assert!(MaybeRegex::new("[0-9]+").matches_at_least("1 22 333", 3));
+        assert!(!MaybeRegex::new("[0-9]{4}").matches_at_least("1234 567", 2));
+    }
+
+    #[test]
+    fn counts_matches() {
+        assert_eq!(MaybeRegex::new("todo").count("TODO: a, todo: b"), 2);
+        assert_eq!(MaybeRegex::new("-todo").count("todo"), 1);
+        assert_eq!(MaybeRegex::new("aa").count("aaaaa"), 2);
+        assert_eq!(MaybeRegex::new("[0-9]+").count("1 22 333"), 3);
+        assert_eq!(
+            MaybeRegex::new("cat")
+                .as_whole_word()
+                .count("cat concat cat"),
+            2
+        );
+        assert_eq!(MaybeRegex::new("x").count(""), 0);
+    }
 }
@@ -0,0 +1,101 @@
+use crate::{MaybeRegex, TagWrapperData, build_regex};
+
+#[derive(Debug, PartialEq)]
+enum LikeToken {
+    Text(String),
+    // `%`
+    AnyRun,
+    // `_`
+    AnyChar,
+}
+
+impl MaybeRegex {
+    /// A SQL `LIKE` pattern: `%` matches any run of characters, `_` matches any single one, and
+    /// the pattern has to match the whole haystack. `\` escapes a wildcard or itself. Like
+    /// `literal`, `-` has no special meaning.
+    ///
+    /// `%text%` is searched for as plain text; anything else is translated to a regex.
+    ///
+    /// ```
+    /// use maybe_regex::MaybeRegex;
+    ///
+    /// assert!(MaybeRegex::like("j_n%").matches("January"));
+    /// assert!(!MaybeRegex::like("jan").matches("January"));
+    /// assert!(!MaybeRegex::like("%\\%%").matches("100 percent"));
+    /// ```
+    pub fn like<S: Into<String>>(s: S) -> Self {
+        let s = s.into();
+        let tokens = like_tokens(&s);
+        let data = match tokens.as_slice() {
+            [LikeToken::AnyRun] => TagWrapperData::Raw(String::new()),
+            [LikeToken::AnyRun, LikeToken::Text(text), LikeToken::AnyRun] => {
+                TagWrapperData::Raw(text.clone())
+            }
+            tokens => {
+                let mut pattern = String::from(r"(?s)\A");
+                for token in tokens {
+                    match token {
+                        LikeToken::Text(text) => pattern.push_str(&regex::escape(text)),
+                        LikeToken::AnyRun => pattern.push_str(".*"),
+                        LikeToken::AnyChar => pattern.push('.'),
+                    }
+                }
+                pattern.push_str(r"\z");
+                match build_regex(&pattern, true) {
+                    Some(regex) => TagWrapperData::Regex(regex),
+                    None => TagWrapperData::Raw(s.clone()),
+                }
+            }
+        };
+        Self::with_data(data, s, false, false)
+    }
+}
+
+// Splits a `LIKE` pattern into runs of text and wildcards, merging repeated `%`s.
+fn like_tokens(s: &str) -> Vec<LikeToken> {
+    let mut tokens = vec![];
+    let mut chars = s.chars();
+    while let Some(c) = chars.next() {
+        let token = match c {
+            '%' if tokens.last() == Some(&LikeToken::AnyRun) => continue,
+            '%' => LikeToken::AnyRun,
+            '_' => LikeToken::AnyChar,
+            // A trailing `\` stands for itself
+            '\\' => LikeToken::Text(chars.next().unwrap_or('\\').to_string()),
+            c => LikeToken::Text(c.to_string()),
+        };
+        match (tokens.last_mut(), token) {
+            (Some(LikeToken::Text(text)), LikeToken::Text(next)) => text.push_str(&next),
+            (_, token) => tokens.push(token),
+        }
+    }
+    tokens
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn translates_wildcards() {
+        let contains = MaybeRegex::like("%disk full%");
+        assert_eq!(contains.as_literal(), Some("disk full"));
+        assert!(contains.matches("error: disk full!"));
+
+        let prefix = MaybeRegex::like("err%");
+        assert!(prefix.is_regex());
+        assert!(prefix.matches("error\nmore") && !prefix.matches("an error"));
+
+        let single = MaybeRegex::like("c_t");
+        assert!(single.matches("cat") && single.matches("CUT") && !single.matches("cart"));
+
+        let exact = MaybeRegex::like("a.b");
+        assert!(exact.matches("a.b") && !exact.matches("axb") && !exact.matches("a.bc"));
+
+        assert!(MaybeRegex::like("50\\%").matches("50%"));
+        assert!(!MaybeRegex::like("50\\%").matches("500"));
+        assert!(MaybeRegex::like("%%").matches(""));
+        assert!(MaybeRegex::like("").matches("") && !MaybeRegex::like("").matches("x"));
+        assert!(!MaybeRegex::like("-x").is_negative);
+    }
+}
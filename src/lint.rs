@@ -0,0 +1,137 @@
+use crate::{DetectionReason, MaybeRegex, TagWrapperData, detect::expand_quoted_spans};
+use regex_syntax::{
+    ParserBuilder,
+    ast::{ErrorKind, parse::Parser},
+};
+use std::fmt::Display;
+
+// Characters that suggest the user meant a regex when they show up in plain text.
+const REGEX_CHARACTERS: &[char] = &['?', '*', '+', '|', '(', ')', '[', ']', '{', '}', '^', '$'];
+
+/// Something about a pattern that probably isn't what the user meant, for showing as a hint
+/// next to where it was typed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum LintWarning {
+    /// It contains a character with a meaning in regexes, but was searched for as plain text,
+    /// e.g. because it read as a question.
+    RegexCharacterInLiteral(char),
+    /// A bracket, brace or parenthesis without its partner kept it from being a regex, so it
+    /// was searched for as plain text.
+    UnbalancedBracket(char),
+    /// It looked like a regex but didn't compile, so it was searched for as plain text.
+    CompileError(String),
+    /// It matches every haystack, e.g. it's empty or `.*`.
+    MatchesEverything,
+    /// It's negative and matches no haystack, e.g. `-` or `-.*`.
+    MatchesNothing,
+}
+
+impl Display for LintWarning {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::RegexCharacterInLiteral(c) => {
+                write!(f, "Contains `{c}` but was treated as plain text.")
+            }
+            Self::UnbalancedBracket(c) => {
+                write!(f, "Unbalanced `{c}`, so it was treated as plain text.")
+            }
+            Self::CompileError(error) => {
+                write!(
+                    f,
+                    "Not a valid regex, so it was treated as plain text: {error}"
+                )
+            }
+            Self::MatchesEverything => write!(f, "Matches everything."),
+            Self::MatchesNothing => write!(f, "Matches nothing."),
+        }
+    }
+}
+
+impl MaybeRegex {
+    /// Warnings about this pattern, most important first. Empty when nothing looks off.
+    pub fn lint(&self) -> Vec<LintWarning> {
+        let mut warnings = vec![];
+        if !self.is_regex() {
+            warnings.extend(self.literal_warning());
+        }
+        if self.matches_everything() {
+            warnings.push(if self.is_negative {
+                LintWarning::MatchesNothing
+            } else {
+                LintWarning::MatchesEverything
+            });
+        }
+        warnings
+    }
+
+    fn literal_warning(&self) -> Option<LintWarning> {
+        let s = expand_quoted_spans(&self.original);
+        if let Err(error) = Parser::new().parse(&s) {
+            let unbalanced = matches!(
+                error.kind(),
+                ErrorKind::ClassUnclosed | ErrorKind::GroupUnclosed | ErrorKind::GroupUnopened
+            );
+            if unbalanced && let Some(c) = s[error.span().start.offset..].chars().next() {
+                return Some(LintWarning::UnbalancedBracket(c));
+            }
+        }
+        match self.detection().reason {
+            DetectionReason::CompileError(error) => Some(LintWarning::CompileError(error)),
+            DetectionReason::NoRegexSyntax
+            | DetectionReason::Question
+            | DetectionReason::StackedRepetition => s
+                .chars()
+                .find(|c| REGEX_CHARACTERS.contains(c))
+                .map(LintWarning::RegexCharacterInLiteral),
+            _ => None,
+        }
+    }
+
+    // Whether the pattern is contained in every haystack. A regex is when it can match the
+    // empty string without depending on what's around it, like `.*` or `x?`, unlike `^$`.
+    fn matches_everything(&self) -> bool {
+        match &self.data {
+            TagWrapperData::Raw(value) => value.is_empty(),
+            TagWrapperData::Regex(regex) => ParserBuilder::new()
+                .build()
+                .parse(regex.as_str())
+                .is_ok_and(|hir| {
+                    let properties = hir.properties();
+                    properties.minimum_len() == Some(0) && properties.look_set().is_empty()
+                }),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn warns_about_surprising_patterns() {
+        let lint = |s: &str| MaybeRegex::new(s).lint();
+        assert_eq!(
+            lint("is this a regex?"),
+            [LintWarning::RegexCharacterInLiteral('?')]
+        );
+        assert_eq!(lint("C++"), [LintWarning::RegexCharacterInLiteral('+')]);
+        assert_eq!(lint("(unclosed"), [LintWarning::UnbalancedBracket('(')]);
+        assert_eq!(lint("[0-9"), [LintWarning::UnbalancedBracket('[')]);
+        assert!(matches!(
+            lint("\\p{Nope}").as_slice(),
+            [LintWarning::CompileError(_)]
+        ));
+        assert_eq!(lint(""), [LintWarning::MatchesEverything]);
+        assert_eq!(lint(".*"), [LintWarning::MatchesEverything]);
+        assert_eq!(lint("-x?"), [LintWarning::MatchesNothing]);
+
+        for fine in ["error", "^$", "=a?", "\\d+", "hello world", "foo\\.bar"] {
+            assert_eq!(lint(fine), [], "{fine}");
+        }
+        assert_eq!(
+            LintWarning::RegexCharacterInLiteral('?').to_string(),
+            "Contains `?` but was treated as plain text."
+        );
+    }
+}
@@ -0,0 +1,160 @@
+use crate::{MaybeRegex, TagWrapperData, scratch::fold};
+use regex_syntax::hir::{Class, Hir, HirKind};
+
+impl MaybeRegex {
+    /// Literal fragments that every match must contain, for pre-filtering candidates with an
+    /// inverted or trigram index before running the full match. An empty result means no
+    /// fragment is guaranteed.
+    ///
+    /// Fragments of case-insensitive patterns are case folded the way matching folds haystacks,
+    /// e.g. `Straße` to `strasse`, so they should be looked up in an index folded the same way.
+    /// Negation is ignored: these describe what the needle requires.
+    pub fn required_literals(&self) -> Vec<String> {
+        let literals = match &self.data {
+            TagWrapperData::Raw(value) => vec![value.clone()],
+            TagWrapperData::Regex(regex) => match regex_syntax::parse(regex.as_str()) {
+                Ok(hir) => required(&hir).required,
+                Err(_) => vec![],
+            },
+        };
+
+        let mut output: Vec<String> = vec![];
+        for literal in literals {
+            let literal = if self.case_sensitive {
+                literal
+            } else {
+                fold(&literal).into_owned()
+            };
+            if !literal.is_empty() && !output.contains(&literal) {
+                output.push(literal);
+            }
+        }
+        // Fragments contained in a longer fragment add nothing
+        output
+            .iter()
+            .filter(|a| {
+                !output
+                    .iter()
+                    .any(|b| b.len() > a.len() && b.contains(a.as_str()))
+            })
+            .cloned()
+            .collect()
+    }
+}
+
+struct Info {
+    // Set when the expression can only ever match this one string.
+    exact: Option<String>,
+    required: Vec<String>,
+}
+
+impl Info {
+    fn exact(s: String) -> Self {
+        Self {
+            required: vec![s.clone()],
+            exact: Some(s),
+        }
+    }
+
+    fn inexact(required: Vec<String>) -> Self {
+        Self {
+            exact: None,
+            required,
+        }
+    }
+}
+
+fn required(hir: &Hir) -> Info {
+    match hir.kind() {
+        HirKind::Empty | HirKind::Look(_) => Info::exact(String::new()),
+        HirKind::Literal(literal) => Info::exact(String::from_utf8_lossy(&literal.0).into_owned()),
+        HirKind::Class(Class::Unicode(class)) => match class.ranges() {
+            [range] if range.start() == range.end() => Info::exact(range.start().to_string()),
+            _ => Info::inexact(vec![]),
+        },
+        HirKind::Class(Class::Bytes(_)) => Info::inexact(vec![]),
+        HirKind::Capture(capture) => required(&capture.sub),
+        HirKind::Repetition(repetition) if repetition.min == 0 => Info::inexact(vec![]),
+        HirKind::Repetition(repetition) => {
+            let sub = required(&repetition.sub);
+            match (sub.exact, repetition.max) {
+                (Some(exact), Some(max)) if max == repetition.min => {
+                    Info::exact(exact.repeat(max as usize))
+                }
+                (Some(exact), _) => Info::inexact(vec![exact.repeat(repetition.min as usize)]),
+                (None, _) => Info::inexact(sub.required),
+            }
+        }
+        HirKind::Concat(hirs) => {
+            let mut required_literals = vec![];
+            let mut run = String::new();
+            let mut all_exact = true;
+            for hir in hirs {
+                let info = required(hir);
+                match info.exact {
+                    Some(exact) => run.push_str(&exact),
+                    None => {
+                        all_exact = false;
+                        required_literals.push(std::mem::take(&mut run));
+                        required_literals.extend(info.required);
+                    }
+                }
+            }
+            if all_exact {
+                Info::exact(run)
+            } else {
+                required_literals.push(run);
+                Info::inexact(required_literals)
+            }
+        }
+        HirKind::Alternation(hirs) => {
+            let branches: Vec<Info> = hirs.iter().map(required).collect();
+            let first = &branches[0];
+            if first.exact.is_some() && branches.iter().all(|b| b.exact == first.exact) {
+                return Info::exact(first.exact.clone().unwrap_or_default());
+            }
+            let common = first
+                .required
+                .iter()
+                .filter(|literal| branches.iter().all(|b| b.required.contains(literal)))
+                .cloned()
+                .collect();
+            Info::inexact(common)
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn extracts_required_literals() {
+        let literals = |s: &str| MaybeRegex::new(s).required_literals();
+        assert_eq!(literals("Hello"), vec!["hello"]);
+        assert_eq!(
+            literals("^error: [0-9]+ in .*\\.rs$"),
+            vec!["error: ", " in ", ".rs"]
+        );
+        assert_eq!(literals("^(foo|bar)baz"), vec!["baz"]);
+        assert_eq!(literals("(ab){2}c+"), vec!["abab", "c"]);
+        assert!(literals(".*").is_empty());
+        assert_eq!(
+            MaybeRegex::new("ID-[0-9]")
+                .as_case_sensitive()
+                .required_literals(),
+            vec!["ID-"]
+        );
+    }
+
+    #[test]
+    fn folds_required_literals_like_matching() {
+        let pattern = MaybeRegex::new("Straße");
+        assert_eq!(pattern.required_literals(), vec!["strasse"]);
+        assert!(pattern.matches("STRASSE"));
+        assert_eq!(
+            MaybeRegex::new("^ﬁle [0-9]").required_literals(),
+            vec!["file "]
+        );
+    }
+}
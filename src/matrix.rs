@@ -0,0 +1,110 @@
+use crate::MaybeRegexSet;
+
+/// A dense grid of booleans, one row per pattern and one column per haystack.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct BitMatrix {
+    rows: usize,
+    cols: usize,
+    bits: Vec<u64>,
+}
+
+impl BitMatrix {
+    pub fn new(rows: usize, cols: usize) -> Self {
+        Self {
+            rows,
+            cols,
+            bits: vec![0; (rows * cols).div_ceil(64)],
+        }
+    }
+
+    pub fn rows(&self) -> usize {
+        self.rows
+    }
+
+    pub fn cols(&self) -> usize {
+        self.cols
+    }
+
+    pub fn get(&self, row: usize, col: usize) -> bool {
+        assert!(row < self.rows && col < self.cols, "index out of bounds");
+        let i = row * self.cols + col;
+        self.bits[i / 64] & (1 << (i % 64)) != 0
+    }
+
+    pub fn set(&mut self, row: usize, col: usize, value: bool) {
+        assert!(row < self.rows && col < self.cols, "index out of bounds");
+        let i = row * self.cols + col;
+        if value {
+            self.bits[i / 64] |= 1 << (i % 64);
+        } else {
+            self.bits[i / 64] &= !(1 << (i % 64));
+        }
+    }
+
+    pub fn row(&self, row: usize) -> impl Iterator<Item = bool> + '_ {
+        (0..self.cols).map(move |col| self.get(row, col))
+    }
+
+    pub fn col(&self, col: usize) -> impl Iterator<Item = bool> + '_ {
+        (0..self.rows).map(move |row| self.get(row, col))
+    }
+
+    pub fn count_row(&self, row: usize) -> usize {
+        self.row(row).filter(|&b| b).count()
+    }
+
+    pub fn count_col(&self, col: usize) -> usize {
+        self.col(col).filter(|&b| b).count()
+    }
+}
+
+/// Which haystacks each pattern in the set `matches`, for faceted filters that need full
+/// membership rather than a single yes/no. Row `i` is the `i`th pattern, column `j` the
+/// `j`th haystack; negative patterns are set where the haystack *doesn't* contain them.
+///
+/// Each haystack is scanned once for all patterns using the set's compiled automata.
+pub fn match_matrix<I, S>(patterns: &MaybeRegexSet, haystacks: I) -> BitMatrix
+where
+    I: IntoIterator<Item = S>,
+    S: AsRef<str>,
+{
+    let columns: Vec<Vec<bool>> = haystacks
+        .into_iter()
+        .map(|haystack| patterns.contained(haystack.as_ref()))
+        .collect();
+
+    let mut matrix = BitMatrix::new(patterns.len(), columns.len());
+    for (col, contained) in columns.into_iter().enumerate() {
+        for (row, (contained, pattern)) in contained.into_iter().zip(patterns.iter()).enumerate() {
+            matrix.set(row, col, contained != pattern.is_negative);
+        }
+    }
+    matrix
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn builds_membership_matrix() {
+        let set = MaybeRegexSet::new(["error", "^warn", "-disk", "[0-9]+"]);
+        let haystacks = ["error 42", "warn: disk", "info", "Error: disk 7"];
+        let matrix = match_matrix(&set, haystacks);
+
+        assert_eq!((matrix.rows(), matrix.cols()), (4, 4));
+        for (row, pattern) in set.iter().enumerate() {
+            for (col, haystack) in haystacks.iter().enumerate() {
+                assert_eq!(matrix.get(row, col), pattern.matches(haystack));
+            }
+        }
+        assert_eq!(
+            matrix.row(0).collect::<Vec<_>>(),
+            [true, false, false, true]
+        );
+        assert_eq!(matrix.count_col(2), 1);
+
+        let limited = set.clone().with_memory_budget(0);
+        assert_eq!(match_matrix(&limited, haystacks), matrix);
+    }
+}
@@ -0,0 +1,155 @@
+use crate::{MaybeRegex, TagWrapperData, build_regex, min_len_of};
+use std::{borrow::Cow, ops::Range};
+use unicode_normalization::{
+    IsNormalized, UnicodeNormalization, char::canonical_combining_class, is_nfc_quick,
+    is_nfkc_quick,
+};
+
+/// A Unicode normalization form, for `MaybeRegex::as_normalized`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Normalization {
+    /// Canonical composition, so e.g. `e` followed by a combining acute accent equals `é`.
+    Nfc,
+    /// Compatibility composition, which also equates e.g. `ﬁ` with `fi` and full-width `Ａ`
+    /// with `A`.
+    Nfkc,
+}
+
+impl Normalization {
+    fn quick(self, chars: impl Iterator<Item = char>) -> IsNormalized {
+        match self {
+            Self::Nfc => is_nfc_quick(chars),
+            Self::Nfkc => is_nfkc_quick(chars),
+        }
+    }
+
+    // Borrows `s` when it's already normalized.
+    pub(crate) fn apply(self, s: &str) -> Cow<'_, str> {
+        if self.quick(s.chars()) == IsNormalized::Yes {
+            return Cow::Borrowed(s);
+        }
+        let normalized: String = match self {
+            Self::Nfc => s.nfc().collect(),
+            Self::Nfkc => s.nfkc().collect(),
+        };
+        if normalized == s {
+            Cow::Borrowed(s)
+        } else {
+            Cow::Owned(normalized)
+        }
+    }
+
+    // Each character of `s` once normalized, with the range of `s` it came from. Normalization
+    // never reaches across a starter that's already normalized, so the text between two of them
+    // is normalized on its own and every character it yields comes from all of it.
+    pub(crate) fn sources(self, s: &str) -> Vec<(char, Range<usize>)> {
+        let mut starts: Vec<_> = s
+            .char_indices()
+            .filter(|&(index, c)| {
+                index == 0
+                    || (canonical_combining_class(c) == 0
+                        && self.quick(std::iter::once(c)) == IsNormalized::Yes)
+            })
+            .map(|(index, _)| index)
+            .collect();
+        starts.push(s.len());
+        starts
+            .windows(2)
+            .flat_map(|run| {
+                let range = run[0]..run[1];
+                let normalized = self.apply(&s[range.clone()]).into_owned();
+                normalized
+                    .chars()
+                    .map(|c| (c, range.clone()))
+                    .collect::<Vec<_>>()
+            })
+            .collect()
+    }
+}
+
+impl MaybeRegex {
+    /// Normalizes the pattern and every haystack to `form` before matching, so text that
+    /// looks the same compares equal however it's encoded, e.g. tags typed on macOS and
+    /// Windows.
+    ///
+    /// Regexes are normalized as written, and rebuilt without options from
+    /// `MaybeRegexBuilder`. Offsets, as from `match_indices`, are into the haystack as given,
+    /// covering every character that normalized into the match.
+    ///
+    /// ```
+    /// use maybe_regex::{MaybeRegex, Normalization};
+    ///
+    /// let pattern = MaybeRegex::new("caf\u{e9}").as_normalized(Normalization::Nfc);
+    /// assert!(pattern.matches("cafe\u{301}"));
+    /// ```
+    pub fn as_normalized(mut self, form: Normalization) -> Self {
+        self.normalization = Some(form);
+        match &self.data {
+            TagWrapperData::Raw(value) => {
+                if let Cow::Owned(value) = form.apply(value) {
+                    self.data = TagWrapperData::Raw(value);
+                    self.refold();
+                }
+            }
+            TagWrapperData::Regex(regex) => {
+                if let Cow::Owned(source) = form.apply(regex.as_str())
+                    && let Some(regex) = build_regex(&source, !self.case_sensitive)
+                {
+                    self.data = TagWrapperData::Regex(regex);
+                    self.min_len = min_len_of(&self.data, true);
+                }
+            }
+        }
+        self
+    }
+
+    pub(crate) fn normalize_haystack<'a>(&self, haystack: &'a str) -> Cow<'a, str> {
+        match self.normalization {
+            Some(form) => form.apply(haystack),
+            None => Cow::Borrowed(haystack),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::MaybeRegexSet;
+
+    #[test]
+    fn normalizes_pattern_and_haystack() {
+        let composed = MaybeRegex::new("caf\u{e9}").as_normalized(Normalization::Nfc);
+        assert!(composed.matches("CAFE\u{301}") && composed.matches("caf\u{e9}"));
+        assert!(!MaybeRegex::new("caf\u{e9}").matches("cafe\u{301}"));
+
+        let decomposed = MaybeRegex::new("^cafe\u{301}$").as_normalized(Normalization::Nfc);
+        assert!(decomposed.is_regex() && decomposed.matches("caf\u{e9}"));
+
+        let compatible = MaybeRegex::new("file").as_normalized(Normalization::Nfkc);
+        assert!(compatible.matches("\u{fb01}le") && compatible.matches("ＦＩＬＥ"));
+        assert!(
+            !MaybeRegex::new("file")
+                .as_normalized(Normalization::Nfc)
+                .matches("ＦＩＬＥ")
+        );
+
+        let set: MaybeRegexSet = [composed, MaybeRegex::new("-tea")].into_iter().collect();
+        assert!(set.matches("cafe\u{301}") && !set.matches("cafe\u{301} tea"));
+        assert!(!set.compile_report().compiled);
+    }
+
+    #[test]
+    fn reports_offsets_into_the_haystack_as_given() {
+        let composed = MaybeRegex::new("caf\u{e9}").as_normalized(Normalization::Nfc);
+        let haystack = "a CAFE\u{301}s";
+        assert_eq!(composed.match_indices(haystack), [(2, 6)]);
+        assert_eq!(composed.find(haystack).unwrap().as_str(), "CAFE\u{301}");
+        let found: Vec<_> = composed.find_iter(haystack).map(|m| m.as_str()).collect();
+        assert_eq!(found, ["CAFE\u{301}"]);
+
+        let compatible = MaybeRegex::new("^fi.e").as_normalized(Normalization::Nfkc);
+        assert_eq!(compatible.find("\u{fb01}le").unwrap().range(), 0..5);
+        let partial = MaybeRegex::new("i").as_normalized(Normalization::Nfkc);
+        assert_eq!(partial.match_indices("\u{fb01}"), [(0, 3)]);
+    }
+}
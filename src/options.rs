@@ -0,0 +1,196 @@
+use crate::{
+    MaybeRegex,
+    scratch::{Folding, with_prepared},
+};
+use regex::{Regex, RegexBuilder};
+use std::{collections::HashMap, ops::Range, sync::RwLock};
+
+/// Where a match must sit in the haystack (or in `MatchOptions::range`, when set).
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Anchor {
+    #[default]
+    Anywhere,
+    Start,
+    End,
+    /// The match must span the whole haystack.
+    Both,
+}
+
+/// Per-call overrides for `MaybeRegex::matches_with`.
+#[derive(Debug, Default, Clone, PartialEq, Eq, Hash)]
+pub struct MatchOptions {
+    /// Overrides the pattern's own case sensitivity.
+    pub case_sensitive: Option<bool>,
+    /// Only counts matches with a word boundary on either side.
+    pub whole_word: bool,
+    pub anchor: Anchor,
+    /// Only searches this byte range of the haystack. A range that's out of bounds or not on
+    /// character boundaries searches nothing.
+    pub range: Option<Range<usize>>,
+}
+
+impl MatchOptions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn case_sensitive(mut self, case_sensitive: bool) -> Self {
+        self.case_sensitive = Some(case_sensitive);
+        self
+    }
+
+    pub fn whole_word(mut self, whole_word: bool) -> Self {
+        self.whole_word = whole_word;
+        self
+    }
+
+    pub fn anchor(mut self, anchor: Anchor) -> Self {
+        self.anchor = anchor;
+        self
+    }
+
+    pub fn range(mut self, range: Range<usize>) -> Self {
+        self.range = Some(range);
+        self
+    }
+}
+
+// Regexes built for option combinations, keyed by their source. Clones start empty, so a
+// pattern modified after cloning never sees the original's variants.
+#[derive(Default)]
+pub(crate) struct VariantCache {
+    str: RwLock<HashMap<String, Option<Regex>>>,
+    bytes: RwLock<HashMap<String, Option<regex::bytes::Regex>>>,
+}
+
+impl Clone for VariantCache {
+    fn clone(&self) -> Self {
+        Self::default()
+    }
+}
+
+impl std::fmt::Debug for VariantCache {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("VariantCache")
+    }
+}
+
+impl VariantCache {
+    pub(crate) fn get(&self, source: String) -> Option<Regex> {
+        cached(&self.str, source, |source| {
+            RegexBuilder::new(source).build().ok()
+        })
+    }
+
+    pub(crate) fn get_bytes(&self, source: String) -> Option<regex::bytes::Regex> {
+        cached(&self.bytes, source, |source| {
+            regex::bytes::RegexBuilder::new(source).build().ok()
+        })
+    }
+}
+
+fn cached<T: Clone>(
+    cache: &RwLock<HashMap<String, Option<T>>>,
+    source: String,
+    build: impl FnOnce(&str) -> Option<T>,
+) -> Option<T> {
+    if let Some(value) = cache
+        .read()
+        .ok()
+        .and_then(|cache| cache.get(&source).cloned())
+    {
+        return value;
+    }
+    let value = build(&source);
+    if let Ok(mut cache) = cache.write() {
+        cache.insert(source, value.clone());
+    }
+    value
+}
+
+impl MaybeRegex {
+    /// Like `matches`, but with `options` overriding the pattern's defaults for this call.
+    /// The regex needed for each combination of options is built once and cached on the
+    /// pattern, so toggling e.g. case sensitivity in a UI doesn't rebuild anything.
+    pub fn matches_with<S: AsRef<str>>(&self, haystack: S, options: &MatchOptions) -> bool {
+        self.is_contained_within_with(haystack.as_ref(), options) != self.is_negative
+    }
+
+    fn is_contained_within_with(&self, haystack: &str, options: &MatchOptions) -> bool {
+        if *options == MatchOptions::default() {
+            return self.is_contained_within(haystack);
+        }
+        let haystack = match &options.range {
+            Some(range) => match haystack.get(range.clone()) {
+                Some(haystack) => haystack,
+                None => return false,
+            },
+            None => haystack,
+        };
+        if haystack.len() < self.min_len {
+            return false;
+        }
+
+        let case_sensitive = options.case_sensitive.unwrap_or(self.case_sensitive);
+        let pattern = self.regex_source();
+        let mut source = format!("(?:{pattern})");
+        if options.whole_word {
+            source = format!(r"\b{source}\b");
+        }
+        if matches!(options.anchor, Anchor::Start | Anchor::Both) {
+            source = format!(r"\A{source}");
+        }
+        if matches!(options.anchor, Anchor::End | Anchor::Both) {
+            source = format!(r"{source}\z");
+        }
+        if !case_sensitive {
+            source = format!("(?i){source}");
+        }
+
+        let Some(regex) = self.variants.get(source) else {
+            return false;
+        };
+        with_prepared(
+            haystack,
+            Folding::None,
+            self.normalize_line_endings,
+            |haystack| regex.is_match(haystack),
+        )
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn overrides_defaults_per_call() {
+        let pattern = MaybeRegex::new("error");
+        let exact_case = MatchOptions::new().case_sensitive(true);
+        assert!(pattern.matches_with("an error here", &exact_case));
+        assert!(!pattern.matches_with("an Error here", &exact_case));
+        assert!(pattern.matches_with("an Error here", &MatchOptions::new()));
+
+        let word = MatchOptions::new().whole_word(true);
+        assert!(pattern.matches_with("an ERROR here", &word));
+        assert!(!pattern.matches_with("errors here", &word));
+
+        let start = MatchOptions::new().anchor(Anchor::Start);
+        assert!(pattern.matches_with("error: x", &start));
+        assert!(!pattern.matches_with("x error", &start));
+        let end = MatchOptions::new().anchor(Anchor::End);
+        assert!(MaybeRegex::new("[a]|ab").matches_with("xab", &end));
+        assert!(
+            MaybeRegex::new("[0-9]+")
+                .matches_with("123", &MatchOptions::new().anchor(Anchor::Both))
+        );
+
+        let range = MatchOptions::new().range(6..11);
+        assert!(pattern.matches_with("xxxxxxerror", &range));
+        assert!(!pattern.matches_with("errorxxxxxx", &range));
+        assert!(!pattern.matches_with("short", &range));
+
+        assert!(!MaybeRegex::new("-error").matches_with("error", &word));
+        assert!(MaybeRegex::new("-error").matches_with("errors", &word));
+    }
+}
@@ -0,0 +1,105 @@
+use crate::{MaybeRegex, TagWrapperData, build_regex, min_len_of};
+use std::{borrow::Cow, path::Path};
+
+/// How paths should be compared when matching with `matches_path`.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum PathMatchMode {
+    /// `Windows` when compiled for Windows, `Unix` everywhere else.
+    #[default]
+    Native,
+    /// Case-insensitive, and `\` and `/` are treated as the same separator.
+    Windows,
+    /// Case-sensitive, separators compared as-is.
+    Unix,
+}
+
+impl PathMatchMode {
+    fn resolve(self) -> Self {
+        match self {
+            Self::Native if cfg!(windows) => Self::Windows,
+            Self::Native => Self::Unix,
+            mode => mode,
+        }
+    }
+}
+
+impl MaybeRegex {
+    /// Configures this pattern for matching paths under the given platform conventions.
+    pub fn as_path_pattern(mut self, mode: PathMatchMode) -> Self {
+        let mode = mode.resolve();
+        self.path_mode = Some(mode);
+        self.case_sensitive = mode == PathMatchMode::Unix;
+        self.data = match self.data {
+            TagWrapperData::Raw(value) if mode == PathMatchMode::Windows => {
+                TagWrapperData::Raw(value.replace('\\', "/").to_lowercase())
+            }
+            TagWrapperData::Regex(regex) => {
+                // Rebuilt from the compiled source, which keeps earlier wrapping like
+                // `as_whole_word` and has no `/.../` delimiters
+                let pattern = match mode {
+                    // An escaped backslash in the pattern is a separator, so it should match '/'
+                    PathMatchMode::Windows => regex.as_str().replace("\\\\", "/"),
+                    _ => regex.as_str().to_string(),
+                };
+                match build_regex(&pattern, !self.case_sensitive) {
+                    Some(rebuilt) => TagWrapperData::Regex(rebuilt),
+                    None => TagWrapperData::Regex(regex),
+                }
+            }
+            data => data,
+        };
+        self.min_len = min_len_of(&self.data, !self.case_sensitive);
+        self.refold();
+        self
+    }
+
+    pub fn matches_path<P: AsRef<Path>>(&self, path: P) -> bool {
+        self.matches(self.normalize_path(path.as_ref()))
+    }
+
+    pub(crate) fn normalize_path<'p>(&self, path: &'p Path) -> Cow<'p, str> {
+        let path = path.to_string_lossy();
+        match self.path_mode {
+            Some(PathMatchMode::Windows) if path.contains('\\') => {
+                Cow::Owned(path.replace('\\', "/"))
+            }
+            _ => path,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn windows_mode_ignores_case_and_separators() {
+        let literal = MaybeRegex::new("foo\\Xyz").as_path_pattern(PathMatchMode::Windows);
+        assert!(literal.matches_path("C:\\FOO\\xyz\\baz.txt"));
+        assert!(literal.matches_path("c:/foo/XYZ/baz.txt"));
+
+        let regex = MaybeRegex::new("foo\\\\bar\\.txt$").as_path_pattern(PathMatchMode::Windows);
+        assert!(regex.matches_path("C:\\Foo\\Bar.TXT"));
+    }
+
+    #[test]
+    fn unix_mode_is_case_sensitive() {
+        let literal = MaybeRegex::new("foo/Bar").as_path_pattern(PathMatchMode::Unix);
+        assert!(literal.matches_path("/home/foo/Bar"));
+        assert!(!literal.matches_path("/home/foo/bar"));
+
+        let regex = MaybeRegex::new("Bar$").as_path_pattern(PathMatchMode::Unix);
+        assert!(!regex.matches_path("/home/foo/bar"));
+    }
+
+    #[test]
+    fn keeps_the_compiled_regex() {
+        let slash = MaybeRegex::new("/Foo/").as_path_pattern(PathMatchMode::Unix);
+        assert!(slash.matches_path("/home/Foo") && !slash.matches_path("/home/foo"));
+
+        let word = MaybeRegex::new("c.t")
+            .as_whole_word()
+            .as_path_pattern(PathMatchMode::Unix);
+        assert!(word.matches_path("/tmp/cat") && !word.matches_path("/tmp/concat"));
+    }
+}
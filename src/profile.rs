@@ -0,0 +1,205 @@
+use crate::{MaybeRegex, MaybeRegexSet};
+use std::{
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+
+// Bucket `i` holds durations of at most 2^i nanoseconds; the last one also takes anything
+// longer.
+const BUCKETS: usize = 40;
+
+/// Elapsed times of one pattern's match calls, in power-of-two nanosecond buckets.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Histogram {
+    buckets: [u64; BUCKETS],
+    pub calls: u64,
+    pub total: Duration,
+    pub max: Duration,
+}
+
+impl Default for Histogram {
+    fn default() -> Self {
+        Self {
+            buckets: [0; BUCKETS],
+            calls: 0,
+            total: Duration::ZERO,
+            max: Duration::ZERO,
+        }
+    }
+}
+
+impl Histogram {
+    fn record(&mut self, elapsed: Duration) {
+        let nanos = elapsed.as_nanos().max(1);
+        let bucket = (128 - (nanos - 1).leading_zeros()) as usize;
+        self.buckets[bucket.min(BUCKETS - 1)] += 1;
+        self.calls += 1;
+        self.total += elapsed;
+        self.max = self.max.max(elapsed);
+    }
+
+    pub fn mean(&self) -> Duration {
+        if self.calls == 0 {
+            return Duration::ZERO;
+        }
+        Duration::from_nanos((self.total.as_nanos() / self.calls as u128) as u64)
+    }
+
+    /// An upper bound on the `quantile` (0.0 to 1.0) of call times, accurate to a factor of
+    /// two.
+    pub fn quantile(&self, quantile: f64) -> Duration {
+        let target = (quantile.clamp(0.0, 1.0) * self.calls as f64).ceil() as u64;
+        let mut seen = 0;
+        for (bucket, count) in self.buckets.iter().enumerate() {
+            seen += count;
+            if seen >= target.max(1) {
+                return Duration::from_nanos(1 << bucket).min(self.max);
+            }
+        }
+        self.max
+    }
+
+    /// `(upper bound, count)` for each non-empty bucket.
+    pub fn buckets(&self) -> impl Iterator<Item = (Duration, u64)> + '_ {
+        self.buckets
+            .iter()
+            .enumerate()
+            .filter(|(_, count)| **count > 0)
+            .map(|(bucket, count)| (Duration::from_nanos(1 << bucket), *count))
+    }
+}
+
+/// Timing for one pattern of a `ProfiledSet`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PatternProfile {
+    /// Index of the pattern in the set.
+    pub index: usize,
+    pub pattern: String,
+    pub histogram: Histogram,
+}
+
+/// A `MaybeRegexSet` that times every pattern on every match call, to find out which
+/// patterns are slow.
+///
+/// Each pattern is checked on its own so its time can be attributed, which is slower than
+/// the compiled set; use it while investigating, not in production.
+#[derive(Debug)]
+pub struct ProfiledSet {
+    set: MaybeRegexSet,
+    histograms: Mutex<Vec<Histogram>>,
+}
+
+impl MaybeRegexSet {
+    pub fn profiled(self) -> ProfiledSet {
+        ProfiledSet {
+            histograms: Mutex::new(vec![Histogram::default(); self.len()]),
+            set: self,
+        }
+    }
+}
+
+impl ProfiledSet {
+    pub fn set(&self) -> &MaybeRegexSet {
+        &self.set
+    }
+
+    pub fn into_inner(self) -> MaybeRegexSet {
+        self.set
+    }
+
+    /// Same result as `MaybeRegexSet::matches`. Every pattern is evaluated, rather than
+    /// stopping at the first decisive one, so each gets a sample.
+    pub fn matches<S: AsRef<str>>(&self, haystack: S) -> bool {
+        let haystack = haystack.as_ref();
+        let contained: Vec<(bool, Duration)> = self
+            .set
+            .iter()
+            .map(|pattern| time(pattern, haystack))
+            .collect();
+
+        if let Ok(mut histograms) = self.histograms.lock() {
+            for (histogram, (_, elapsed)) in histograms.iter_mut().zip(&contained) {
+                histogram.record(*elapsed);
+            }
+        }
+
+        let mut has_positive = false;
+        let mut any_positive_matched = false;
+        for (pattern, (contained, _)) in self.set.iter().zip(contained) {
+            if pattern.is_negative {
+                if contained {
+                    return false;
+                }
+            } else {
+                has_positive = true;
+                any_positive_matched |= contained;
+            }
+        }
+        any_positive_matched || !has_positive
+    }
+
+    /// Timings so far, slowest pattern (by total time) first.
+    pub fn profile_report(&self) -> Vec<PatternProfile> {
+        let histograms = match self.histograms.lock() {
+            Ok(histograms) => histograms.clone(),
+            Err(_) => return vec![],
+        };
+        let mut report: Vec<_> = self
+            .set
+            .iter()
+            .zip(histograms)
+            .enumerate()
+            .map(|(index, (pattern, histogram))| PatternProfile {
+                index,
+                pattern: pattern.to_string(),
+                histogram,
+            })
+            .collect();
+        report.sort_by(|a, b| {
+            b.histogram
+                .total
+                .cmp(&a.histogram.total)
+                .then(a.index.cmp(&b.index))
+        });
+        report
+    }
+
+    pub fn reset(&self) {
+        if let Ok(mut histograms) = self.histograms.lock() {
+            histograms.fill(Histogram::default());
+        }
+    }
+}
+
+fn time(pattern: &MaybeRegex, haystack: &str) -> (bool, Duration) {
+    let start = Instant::now();
+    let contained = pattern.is_contained_within(haystack);
+    (contained, start.elapsed())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn records_time_per_pattern() {
+        let set = MaybeRegexSet::new(["error", "-ignored", "(\\w+\\s*)+=.*[0-9]"]);
+        let profiled = set.clone().profiled();
+        let haystacks = ["error: x", "error: ignored", "a = 1", "info"];
+        for haystack in haystacks {
+            assert_eq!(profiled.matches(haystack), set.matches(haystack));
+        }
+
+        let report = profiled.profile_report();
+        assert_eq!(report.len(), 3);
+        assert!(report.iter().all(|profile| profile.histogram.calls == 4));
+        assert!(report[0].histogram.total >= report[2].histogram.total);
+        let histogram = &report[0].histogram;
+        assert_eq!(histogram.buckets().map(|(_, count)| count).sum::<u64>(), 4);
+        assert!(histogram.quantile(0.5) <= histogram.quantile(1.0));
+        assert!(histogram.quantile(1.0) <= histogram.max);
+
+        profiled.reset();
+        assert_eq!(profiled.profile_report()[0].histogram.calls, 0);
+    }
+}
@@ -0,0 +1,160 @@
+use crate::{MaybeRegex, TagWrapperData};
+use rand::{Rng, RngCore, SeedableRng, rngs::StdRng, seq::IndexedRandom};
+use regex_syntax::hir::{Class, Hir, HirKind};
+
+// Unbounded repetitions (`*`, `+`, `{n,}`) are capped at this many extra iterations.
+const MAX_EXTRA_REPETITIONS: u32 = 3;
+const MAX_ATTEMPTS: usize = 32;
+
+impl MaybeRegex {
+    /// Generates an example haystack that `matches` accepts. For negative patterns this is a
+    /// near-miss: a match of the underlying pattern that has been altered until it no longer
+    /// contains it.
+    ///
+    /// The output is deterministic for a given seed and version of `rand`. Returns `None` if no
+    /// example could be found, e.g. for patterns that can never match.
+    pub fn random_match(&self, seed: u64) -> Option<String> {
+        let mut rng = StdRng::seed_from_u64(seed);
+        let hir = match &self.data {
+            TagWrapperData::Raw(_) => None,
+            TagWrapperData::Regex(regex) => regex_syntax::parse(regex.as_str()).ok(),
+        };
+
+        for _ in 0..MAX_ATTEMPTS {
+            let mut sample = match &hir {
+                Some(hir) => {
+                    let mut sample = String::new();
+                    generate(hir, &mut rng, &mut sample);
+                    sample
+                }
+                None => self.as_literal()?.to_string(),
+            };
+            if self.is_negative {
+                sample = near_miss(self, sample, &mut rng)?;
+            }
+            if self.matches(&sample) {
+                return Some(sample);
+            }
+        }
+        None
+    }
+
+    /// Generates up to `count` distinct examples, see `random_match`.
+    pub fn random_matches(&self, seed: u64, count: usize) -> Vec<String> {
+        let mut rng = StdRng::seed_from_u64(seed);
+        let mut samples = vec![];
+        for _ in 0..count * MAX_ATTEMPTS {
+            if samples.len() == count {
+                break;
+            }
+            if let Some(sample) = self.random_match(rng.next_u64())
+                && !samples.contains(&sample)
+            {
+                samples.push(sample);
+            }
+        }
+        samples
+    }
+}
+
+fn generate(hir: &Hir, rng: &mut StdRng, output: &mut String) {
+    match hir.kind() {
+        HirKind::Empty | HirKind::Look(_) => {}
+        HirKind::Literal(literal) => output.push_str(&String::from_utf8_lossy(&literal.0)),
+        HirKind::Class(Class::Unicode(class)) => {
+            let ranges = class.ranges();
+            // Prefer printable ASCII so samples are readable
+            let printable: Vec<(char, char)> = ranges
+                .iter()
+                .filter_map(|range| {
+                    let start = range.start().max(' ');
+                    let end = range.end().min('~');
+                    (start <= end).then_some((start, end))
+                })
+                .collect();
+            let candidates: Vec<(char, char)> = if printable.is_empty() {
+                ranges.iter().map(|r| (r.start(), r.end())).collect()
+            } else {
+                printable
+            };
+            if let Some(&(start, end)) = candidates.choose(rng) {
+                let code = rng.random_range(start as u32..=end as u32);
+                output.push(char::from_u32(code).unwrap_or(start));
+            }
+        }
+        HirKind::Class(Class::Bytes(class)) => {
+            let ranges: Vec<(u8, u8)> = class
+                .ranges()
+                .iter()
+                .map(|r| (r.start(), r.end()))
+                .collect();
+            if let Some(&(start, end)) = ranges.choose(rng) {
+                output.push(rng.random_range(start..=end) as char);
+            }
+        }
+        HirKind::Repetition(repetition) => {
+            let max = repetition
+                .max
+                .unwrap_or(repetition.min + MAX_EXTRA_REPETITIONS);
+            let count = rng.random_range(repetition.min..=max);
+            for _ in 0..count {
+                generate(&repetition.sub, rng, output);
+            }
+        }
+        HirKind::Capture(capture) => generate(&capture.sub, rng, output),
+        HirKind::Concat(hirs) => {
+            for hir in hirs {
+                generate(hir, rng, output);
+            }
+        }
+        HirKind::Alternation(hirs) => {
+            if let Some(hir) = hirs.choose(rng) {
+                generate(hir, rng, output);
+            }
+        }
+    }
+}
+
+fn near_miss(pattern: &MaybeRegex, sample: String, rng: &mut StdRng) -> Option<String> {
+    const REPLACEMENTS: &[char] = &['x', 'q', 'z', '0', '7', '_', ' '];
+
+    let mut chars: Vec<char> = sample.chars().collect();
+    for _ in 0..MAX_ATTEMPTS {
+        if !pattern.is_contained_within(chars.iter().collect::<String>()) {
+            return Some(chars.into_iter().collect());
+        }
+        if chars.is_empty() {
+            return None;
+        }
+        let index = rng.random_range(..chars.len());
+        chars[index] = *REPLACEMENTS.choose(rng)?;
+    }
+    None
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn generates_matching_samples() {
+        let pattern = MaybeRegex::new("^ticket-[0-9]{3,5}$");
+        for sample in pattern.random_matches(42, 5) {
+            assert!(pattern.matches(&sample), "{sample}");
+        }
+        assert_eq!(pattern.random_match(7), pattern.random_match(7));
+        assert_eq!(MaybeRegex::new("hello").random_match(1).unwrap(), "hello");
+        assert_eq!(
+            MaybeRegex::new("foo\\.bar").random_match(1).unwrap(),
+            "foo.bar"
+        );
+    }
+
+    #[test]
+    fn generates_near_misses_for_negatives() {
+        let pattern = MaybeRegex::new("-error");
+        let sample = pattern.random_match(3).unwrap();
+        assert!(pattern.matches(&sample));
+        assert_eq!(sample.len(), "error".len());
+    }
+}
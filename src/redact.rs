@@ -0,0 +1,39 @@
+use crate::MaybeRegex;
+
+// Fixed so that redacted output doesn't reveal how long the secret was.
+const REDACTION_LEN: usize = 4;
+
+impl MaybeRegex {
+    /// Replaces every match with a fixed-width mask, e.g. `****`.
+    pub fn redact<S: AsRef<str>>(&self, text: S, mask_char: char) -> String {
+        let mask: String = std::iter::repeat_n(mask_char, REDACTION_LEN).collect();
+        self.replace_spans(text.as_ref(), |_| mask.clone())
+    }
+
+    /// Replaces every character of every match with `mask_char`, so the text keeps its
+    /// layout. Lengths are counted in characters, not bytes.
+    pub fn redact_preserving_length<S: AsRef<str>>(&self, text: S, mask_char: char) -> String {
+        self.replace_spans(text.as_ref(), |matched| {
+            matched.chars().map(|_| mask_char).collect()
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn redacts_matches() {
+        let token = MaybeRegex::new("token=[a-z0-9]+");
+        assert_eq!(token.redact("auth token=abc123 ok", '*'), "auth **** ok");
+        assert_eq!(
+            token.redact_preserving_length("a token=x9 b token=zz", '#'),
+            "a ######## b ########"
+        );
+        assert_eq!(
+            MaybeRegex::new("secret").redact_preserving_length("my SECRET é", 'x'),
+            "my xxxxxx é"
+        );
+    }
+}
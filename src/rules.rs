@@ -0,0 +1,160 @@
+use crate::MaybeRegex;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum RuleAction {
+    Allow,
+    Deny,
+}
+
+/// Which matching rule decides the outcome when several match.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum EvaluationOrder {
+    /// The first matching rule wins, like firewall rules.
+    #[default]
+    FirstMatchWins,
+    /// The last matching rule wins, like gitignore, so exceptions can be appended to the end.
+    LastMatchWins,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Rule {
+    pub action: RuleAction,
+    pub pattern: MaybeRegex,
+}
+
+impl Rule {
+    pub fn allow<S: AsRef<str>>(pattern: S) -> Self {
+        Self {
+            action: RuleAction::Allow,
+            pattern: MaybeRegex::new(pattern),
+        }
+    }
+
+    pub fn deny<S: AsRef<str>>(pattern: S) -> Self {
+        Self {
+            action: RuleAction::Deny,
+            pattern: MaybeRegex::new(pattern),
+        }
+    }
+}
+
+/// An ordered list of allow/deny rules. By default they're evaluated like firewall rules or
+/// ACLs: rules are tried from the top and the first one whose pattern `matches` decides the
+/// outcome. With `EvaluationOrder::LastMatchWins` the last matching rule decides instead. If
+/// none match, the default action applies.
+///
+/// A rule's pattern may itself be negative, e.g. `Rule::deny("-@example.com")` denies
+/// everything that doesn't contain `@example.com`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RuleList {
+    rules: Vec<Rule>,
+    default: RuleAction,
+    order: EvaluationOrder,
+}
+
+impl RuleList {
+    pub fn new(default: RuleAction) -> Self {
+        Self {
+            rules: vec![],
+            default,
+            order: EvaluationOrder::default(),
+        }
+    }
+
+    pub fn with_order(mut self, order: EvaluationOrder) -> Self {
+        self.order = order;
+        self
+    }
+
+    pub fn order(&self) -> EvaluationOrder {
+        self.order
+    }
+
+    pub fn allow<S: AsRef<str>>(mut self, pattern: S) -> Self {
+        self.rules.push(Rule::allow(pattern));
+        self
+    }
+
+    pub fn deny<S: AsRef<str>>(mut self, pattern: S) -> Self {
+        self.rules.push(Rule::deny(pattern));
+        self
+    }
+
+    pub fn push(&mut self, rule: Rule) {
+        self.rules.push(rule);
+    }
+
+    pub fn rules(&self) -> &[Rule] {
+        &self.rules
+    }
+
+    pub fn default_action(&self) -> RuleAction {
+        self.default
+    }
+
+    /// The index and rule that decides the outcome for this haystack, if any rule matches.
+    pub fn matching_rule<S: AsRef<str>>(&self, haystack: S) -> Option<(usize, &Rule)> {
+        let haystack = haystack.as_ref();
+        let mut rules = self.rules.iter().enumerate();
+        match self.order {
+            EvaluationOrder::FirstMatchWins => {
+                rules.find(|(_, rule)| rule.pattern.matches(haystack))
+            }
+            EvaluationOrder::LastMatchWins => {
+                rules.rfind(|(_, rule)| rule.pattern.matches(haystack))
+            }
+        }
+    }
+
+    pub fn evaluate<S: AsRef<str>>(&self, haystack: S) -> RuleAction {
+        match self.matching_rule(haystack) {
+            Some((_, rule)) => rule.action,
+            None => self.default,
+        }
+    }
+
+    pub fn is_allowed<S: AsRef<str>>(&self, haystack: S) -> bool {
+        self.evaluate(haystack) == RuleAction::Allow
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn first_match_wins() {
+        let rules = RuleList::new(RuleAction::Deny)
+            .allow("^/public/")
+            .deny("\\.secret$")
+            .allow("\\.txt$");
+
+        assert!(rules.is_allowed("/public/notes.secret"));
+        assert!(!rules.is_allowed("/home/notes.secret"));
+        assert!(rules.is_allowed("/home/notes.txt"));
+        assert!(!rules.is_allowed("/home/notes.md"));
+        assert_eq!(rules.matching_rule("/home/a.txt").map(|(i, _)| i), Some(2));
+    }
+
+    #[test]
+    fn negative_patterns_match_absence() {
+        let rules = RuleList::new(RuleAction::Allow).deny("-@example.com");
+        assert!(rules.is_allowed("alice@example.com"));
+        assert!(!rules.is_allowed("mallory@evil.test"));
+    }
+
+    #[test]
+    fn last_match_wins() {
+        let rules = RuleList::new(RuleAction::Allow)
+            .with_order(EvaluationOrder::LastMatchWins)
+            .deny("\\.log$")
+            .allow("important\\.log$");
+
+        assert!(!rules.is_allowed("debug.log"));
+        assert!(rules.is_allowed("important.log"));
+        assert_eq!(
+            rules.matching_rule("important.log").map(|(i, _)| i),
+            Some(1)
+        );
+    }
+}
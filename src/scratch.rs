@@ -0,0 +1,267 @@
+use crate::MaybeRegex;
+use std::{borrow::Cow, cell::RefCell, ops::Range};
+
+/// A reusable buffer for the haystack transformations matching needs, namely case folding for
+/// case-insensitive patterns and CRLF normalization.
+///
+/// Matching normally uses a thread-local buffer, so this is only needed by callers who want
+/// to manage buffers themselves, e.g. one per worker in an arena.
+#[derive(Debug, Default, Clone)]
+pub struct Scratch {
+    buffer: String,
+}
+
+// How `prepare` folds the case of a haystack.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub(crate) enum Folding {
+    // Case sensitive, or left to a case-insensitive regex or automaton
+    None,
+    Ascii,
+    Unicode,
+}
+
+// Buffers that grew past this while handling an unusually large haystack are released rather
+// than kept around for the life of the thread.
+const MAX_RETAINED_CAPACITY: usize = 1 << 20;
+
+thread_local! {
+    static SCRATCH: RefCell<Scratch> = RefCell::default();
+}
+
+impl Scratch {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self {
+            buffer: String::with_capacity(capacity),
+        }
+    }
+
+    // The haystack as a needle sees it. Borrows `haystack` directly when no transformation
+    // is needed.
+    pub(crate) fn prepare<'s>(
+        &'s mut self,
+        haystack: &'s str,
+        folding: Folding,
+        normalize_line_endings: bool,
+    ) -> &'s str {
+        let normalize = normalize_line_endings && haystack.contains("\r\n");
+        if folding == Folding::None && !normalize {
+            return haystack;
+        }
+
+        self.buffer.clear();
+        let mut chars = haystack.chars().peekable();
+        while let Some(c) = chars.next() {
+            if normalize && c == '\r' && chars.peek() == Some(&'\n') {
+                continue;
+            }
+            match folding {
+                Folding::None => self.buffer.push(c),
+                Folding::Ascii => self.buffer.push(c.to_ascii_lowercase()),
+                Folding::Unicode => push_folded(&mut self.buffer, c),
+            }
+        }
+        &self.buffer
+    }
+}
+
+// An owned copy of the prepared haystack, for iterators that outlive a borrowed buffer, or
+// `None` when no transformation is needed.
+pub(crate) fn prepared_copy(
+    haystack: &str,
+    folding: Folding,
+    normalize_line_endings: bool,
+) -> Option<String> {
+    let mut scratch = Scratch::default();
+    let prepared = scratch.prepare(haystack, folding, normalize_line_endings);
+    (!std::ptr::eq(prepared, haystack)).then_some(scratch.buffer)
+}
+
+// Maps offsets in a prepared haystack back to the haystack it was prepared from. Holds where
+// each kept character starts in the prepared haystack, and where the text it came from starts
+// and ends in the original, followed by both lengths.
+pub(crate) struct OffsetMap(Vec<(usize, usize, usize)>);
+
+impl OffsetMap {
+    // `chars` are the haystack's characters before folding, each with the range of the
+    // haystack it came from, which is more than the character itself after normalization.
+    pub(crate) fn new(
+        chars: impl IntoIterator<Item = (char, Range<usize>)>,
+        len: usize,
+        folding: Folding,
+        normalize_line_endings: bool,
+    ) -> Self {
+        let mut map = Vec::with_capacity(len + 1);
+        let mut prepared = 0;
+        let mut folded = String::new();
+        let mut chars = chars.into_iter().peekable();
+        while let Some((c, source)) = chars.next() {
+            if normalize_line_endings && c == '\r' && chars.peek().is_some_and(|(c, _)| *c == '\n')
+            {
+                continue;
+            }
+            map.push((prepared, source.start, source.end));
+            prepared += if folding == Folding::Unicode {
+                folded.clear();
+                push_folded(&mut folded, c);
+                folded.len()
+            } else {
+                c.len_utf8()
+            };
+        }
+        map.push((prepared, len, len));
+        Self(map)
+    }
+
+    // A match in the prepared haystack as `(start, len)` in the original. A match covering
+    // part of a character's folded form, like one `s` of `ß`, covers the whole character.
+    pub(crate) fn to_original(&self, (start, len): (usize, usize)) -> (usize, usize) {
+        let containing = |offset: usize| &self.0[self.0.partition_point(|e| e.0 <= offset) - 1];
+        let original_start = containing(start).1;
+        if len == 0 {
+            return (original_start, 0);
+        }
+        (
+            original_start,
+            containing(start + len - 1).2 - original_start,
+        )
+    }
+}
+
+// Case folds text the way `prepare` folds haystacks, borrowing it when folding doesn't change
+// it.
+pub(crate) fn fold(s: &str) -> Cow<'_, str> {
+    if s.chars().all(folds_to_itself) {
+        return Cow::Borrowed(s);
+    }
+    let mut folded = String::with_capacity(s.len());
+    for c in s.chars() {
+        push_folded(&mut folded, c);
+    }
+    Cow::Owned(folded)
+}
+
+// Lowercases ASCII letters only, for patterns promised ASCII haystacks.
+pub(crate) fn fold_ascii(s: &str) -> Cow<'_, str> {
+    if s.bytes().any(|b| b.is_ascii_uppercase()) {
+        Cow::Owned(s.to_ascii_lowercase())
+    } else {
+        Cow::Borrowed(s)
+    }
+}
+
+// Unicode case folding: lowercasing, plus the characters whose lowercase form still differs
+// from others with the same fold, like `ς` and `σ`, or that fold to several characters, like
+// `ß` to `ss`.
+fn push_folded(buffer: &mut String, c: char) {
+    match c {
+        'ß' | 'ẞ' => buffer.push_str("ss"),
+        'ﬀ' => buffer.push_str("ff"),
+        'ﬁ' => buffer.push_str("fi"),
+        'ﬂ' => buffer.push_str("fl"),
+        'ﬃ' => buffer.push_str("ffi"),
+        'ﬄ' => buffer.push_str("ffl"),
+        'ﬅ' | 'ﬆ' => buffer.push_str("st"),
+        _ => buffer.push(match c {
+            'ς' => 'σ',
+            'ſ' => 's',
+            'ϐ' => 'β',
+            'ϑ' => 'θ',
+            'ϕ' => 'φ',
+            'ϖ' => 'π',
+            'ϰ' => 'κ',
+            'ϱ' => 'ρ',
+            'ϵ' => 'ε',
+            'ẛ' => 'ṡ',
+            '\u{1FBE}' => 'ι',
+            c => {
+                buffer.extend(c.to_lowercase());
+                return;
+            }
+        }),
+    }
+}
+
+fn folds_to_itself(c: char) -> bool {
+    !matches!(
+        c,
+        'ß' | 'ẞ' | 'ﬀ'
+            ..='ﬆ' | 'ς' | 'ſ' | 'ϐ' | 'ϑ' | 'ϕ' | 'ϖ' | 'ϰ' | 'ϱ' | 'ϵ' | 'ẛ' | '\u{1FBE}'
+    ) && c.to_lowercase().eq([c])
+}
+
+// Runs `f` on the prepared haystack using this thread's scratch buffer.
+pub(crate) fn with_prepared<R>(
+    haystack: &str,
+    folding: Folding,
+    normalize_line_endings: bool,
+    f: impl FnOnce(&str) -> R,
+) -> R {
+    SCRATCH.with(|scratch| match scratch.try_borrow_mut() {
+        Ok(mut scratch) => {
+            let output = f(scratch.prepare(haystack, folding, normalize_line_endings));
+            if scratch.buffer.capacity() > MAX_RETAINED_CAPACITY {
+                *scratch = Scratch::default();
+            }
+            output
+        }
+        // Already in use further up the stack
+        Err(_) => f(Scratch::default().prepare(haystack, folding, normalize_line_endings)),
+    })
+}
+
+impl MaybeRegex {
+    /// Like `is_contained_within`, but using the caller's `scratch` buffer.
+    pub fn is_contained_within_with_scratch<S: AsRef<str>>(
+        &self,
+        haystack: S,
+        scratch: &mut Scratch,
+    ) -> bool {
+        let haystack = haystack.as_ref();
+        #[cfg(feature = "normalization")]
+        let haystack = &*self.normalize_haystack(haystack);
+        let haystack = scratch.prepare(haystack, self.folding(), self.normalize_line_endings);
+        self.is_contained_within_prepared(haystack)
+    }
+
+    /// Like `matches`, but using the caller's `scratch` buffer.
+    pub fn matches_with_scratch<S: AsRef<str>>(&self, haystack: S, scratch: &mut Scratch) -> bool {
+        self.is_contained_within_with_scratch(haystack, scratch) != self.is_negative
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn prepares_into_reused_buffer() {
+        let mut scratch = Scratch::with_capacity(64);
+        assert_eq!(scratch.prepare("A\r\nB", Folding::Unicode, true), "a\nb");
+        assert_eq!(scratch.prepare("A\r\nB", Folding::None, false), "A\r\nB");
+        assert_eq!(scratch.prepare("ÉCOLE", Folding::Unicode, false), "école");
+        assert_eq!(scratch.prepare("ÉCOLE", Folding::Ascii, false), "École");
+        assert!(scratch.buffer.capacity() >= 64);
+        assert_eq!(
+            scratch.prepare("STRAẞE ΟΔΟΣ ﬁle", Folding::Unicode, false),
+            "strasse οδοσ file"
+        );
+        assert_eq!(fold("ſtraße ὁδός"), "strasse ὁδόσ");
+        assert!(matches!(fold("plain"), Cow::Borrowed("plain")));
+
+        let pattern = MaybeRegex::new("école");
+        assert!(pattern.matches_with_scratch("L'ÉCOLE", &mut scratch));
+        assert!(!MaybeRegex::new("-école").matches_with_scratch("L'ÉCOLE", &mut scratch));
+        assert_eq!(
+            with_prepared("ABC", Folding::Unicode, false, |outer| {
+                with_prepared("DEF", Folding::Unicode, false, |inner| {
+                    format!("{outer}{inner}")
+                })
+            }),
+            "abcdef"
+        );
+    }
+}
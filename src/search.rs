@@ -0,0 +1,352 @@
+use crate::{Encoding, MaybeRegex, MaybeRegexSet, SpanOptions, encoding::decode};
+use std::{
+    fs::File,
+    io::{self, BufRead, BufReader},
+    ops::Range,
+    path::{Path, PathBuf},
+    sync::{
+        Arc,
+        atomic::{AtomicBool, Ordering},
+    },
+    time::{Duration, Instant},
+};
+
+/// A cheap, cloneable flag for aborting a search from another thread, e.g. when the user
+/// edits the query before the previous search has finished.
+#[derive(Debug, Clone, Default)]
+pub struct CancellationToken(Arc<AtomicBool>);
+
+impl CancellationToken {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn cancel(&self) {
+        self.0.store(true, Ordering::Relaxed);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.0.load(Ordering::Relaxed)
+    }
+}
+
+/// A matching line. `spans` are byte ranges within `line` and are empty for lines that only
+/// match because they don't contain a negative pattern.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LineMatch {
+    pub path: Option<PathBuf>,
+    pub line_number: usize,
+    pub line: String,
+    pub spans: Vec<Range<usize>>,
+}
+
+/// The matches found by a search, and whether it stopped early because it was cancelled.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SearchResults {
+    pub matches: Vec<LineMatch>,
+    pub cancelled: bool,
+}
+
+/// A snapshot of how far a search has got, passed to the progress callback.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Progress {
+    pub bytes_processed: u64,
+    pub files_done: usize,
+    pub matches: usize,
+}
+
+type ProgressCallback<'p> = Arc<dyn Fn(&Progress) + Send + Sync + 'p>;
+
+#[derive(Debug, Clone, Copy)]
+enum Matcher<'p> {
+    Single(&'p MaybeRegex),
+    Set(&'p MaybeRegexSet),
+}
+
+impl Matcher<'_> {
+    fn find(&self, line: &str) -> Option<Vec<Range<usize>>> {
+        match self {
+            Self::Single(pattern) => pattern.matches(line).then(|| {
+                pattern
+                    .spans(line)
+                    .into_iter()
+                    .filter(|_| !pattern.is_negative)
+                    .collect()
+            }),
+            Self::Set(set) => set.matches(line).then(|| {
+                set.iter()
+                    .filter(|pattern| !pattern.is_negative)
+                    .flat_map(|pattern| pattern.spans(line))
+                    .collect()
+            }),
+        }
+    }
+}
+
+/// Line-oriented search over readers and files.
+#[derive(Clone)]
+pub struct Searcher<'p> {
+    matcher: Matcher<'p>,
+    cancellation: Option<CancellationToken>,
+    progress: Option<(Duration, ProgressCallback<'p>)>,
+    span_options: SpanOptions,
+    encoding: Encoding,
+}
+
+impl std::fmt::Debug for Searcher<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Searcher")
+            .field("matcher", &self.matcher)
+            .field("cancellation", &self.cancellation)
+            .field("span_options", &self.span_options)
+            .field("encoding", &self.encoding)
+            .field("progress_interval", &self.progress.as_ref().map(|(i, _)| i))
+            .finish()
+    }
+}
+
+// Per-search state, so a single `Searcher` can run several searches at once.
+struct Run {
+    results: SearchResults,
+    progress: Progress,
+    last_report: Instant,
+}
+
+impl<'p> Searcher<'p> {
+    pub fn new(pattern: &'p MaybeRegex) -> Self {
+        Self {
+            matcher: Matcher::Single(pattern),
+            cancellation: None,
+            progress: None,
+            span_options: SpanOptions::default(),
+            encoding: Encoding::default(),
+        }
+    }
+
+    pub fn for_set(set: &'p MaybeRegexSet) -> Self {
+        Self {
+            matcher: Matcher::Set(set),
+            cancellation: None,
+            progress: None,
+            span_options: SpanOptions::default(),
+            encoding: Encoding::default(),
+        }
+    }
+
+    /// Checks `token` before each line; once it's cancelled the search stops and returns what
+    /// it found so far.
+    pub fn with_cancellation(mut self, token: CancellationToken) -> Self {
+        self.cancellation = Some(token);
+        self
+    }
+
+    pub fn with_span_options(mut self, options: SpanOptions) -> Self {
+        self.span_options = options;
+        self
+    }
+
+    /// Defaults to `Encoding::Auto`, which skips byte-order marks and, with the `encoding`
+    /// feature, transcodes UTF-16 input that has one.
+    pub fn with_encoding(mut self, encoding: Encoding) -> Self {
+        self.encoding = encoding;
+        self
+    }
+
+    /// Calls `callback` at most once per `interval` while searching, and once more when the
+    /// search finishes or is cancelled, so callers can render a progress bar.
+    pub fn with_progress<F>(mut self, interval: Duration, callback: F) -> Self
+    where
+        F: Fn(&Progress) + Send + Sync + 'p,
+    {
+        self.progress = Some((interval, Arc::new(callback)));
+        self
+    }
+
+    pub fn search_reader<R: BufRead>(&self, reader: R) -> io::Result<SearchResults> {
+        let mut run = Run::new();
+        self.search_into(reader, None, &mut run)?;
+        Ok(self.finish(run))
+    }
+
+    pub fn search_file<P: AsRef<Path>>(&self, path: P) -> io::Result<SearchResults> {
+        self.search_files([path])
+    }
+
+    pub fn search_files<I, P>(&self, paths: I) -> io::Result<SearchResults>
+    where
+        I: IntoIterator<Item = P>,
+        P: AsRef<Path>,
+    {
+        let mut run = Run::new();
+        for path in paths {
+            let path = path.as_ref();
+            let reader = BufReader::new(File::open(path)?);
+            self.search_into(reader, Some(path), &mut run)?;
+            if run.results.cancelled {
+                break;
+            }
+            run.progress.files_done += 1;
+        }
+        Ok(self.finish(run))
+    }
+
+    fn is_cancelled(&self) -> bool {
+        self.cancellation
+            .as_ref()
+            .is_some_and(CancellationToken::is_cancelled)
+    }
+
+    fn report(&self, run: &mut Run, force: bool) {
+        if let Some((interval, callback)) = &self.progress
+            && (force || run.last_report.elapsed() >= *interval)
+        {
+            run.last_report = Instant::now();
+            callback(&run.progress);
+        }
+    }
+
+    fn finish(&self, mut run: Run) -> SearchResults {
+        self.report(&mut run, true);
+        run.results
+    }
+
+    fn search_into<R: BufRead>(
+        &self,
+        reader: R,
+        path: Option<&Path>,
+        run: &mut Run,
+    ) -> io::Result<()> {
+        let mut reader = decode(reader, self.encoding)?;
+        let mut buffer = vec![];
+        let mut line_number = 0;
+        loop {
+            if self.is_cancelled() {
+                run.results.cancelled = true;
+                return Ok(());
+            }
+
+            buffer.clear();
+            let read = reader.read_until(b'\n', &mut buffer)?;
+            if read == 0 {
+                return Ok(());
+            }
+            line_number += 1;
+            run.progress.bytes_processed += read as u64;
+
+            let line = String::from_utf8_lossy(&buffer);
+            let line = line.trim_end_matches(['\n', '\r']);
+            if let Some(spans) = self.matcher.find(line) {
+                run.results.matches.push(LineMatch {
+                    path: path.map(Path::to_path_buf),
+                    line_number,
+                    line: line.to_string(),
+                    spans: self.span_options.apply(spans),
+                });
+                run.progress.matches += 1;
+            }
+            self.report(run, false);
+        }
+    }
+}
+
+impl Run {
+    fn new() -> Self {
+        Self {
+            results: SearchResults::default(),
+            progress: Progress::default(),
+            last_report: Instant::now(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const LOG: &str = "info: started\nerror: disk full\r\nwarn: slow\nerror: timeout\n";
+
+    #[test]
+    fn searches_lines() {
+        let pattern = MaybeRegex::new("^error");
+        let results = Searcher::new(&pattern)
+            .search_reader(LOG.as_bytes())
+            .unwrap();
+        assert!(!results.cancelled);
+        let lines: Vec<(usize, &str)> = results
+            .matches
+            .iter()
+            .map(|m| (m.line_number, m.line.as_str()))
+            .collect();
+        assert_eq!(lines, vec![(2, "error: disk full"), (4, "error: timeout")]);
+        assert_eq!(results.matches[0].spans, vec![0..5]);
+
+        let set = MaybeRegexSet::new(["error", "warn", "-timeout"]);
+        let results = Searcher::for_set(&set)
+            .search_reader(LOG.as_bytes())
+            .unwrap();
+        assert_eq!(results.matches.len(), 2);
+
+        let set = MaybeRegexSet::new(["disk", "disk full", "full"]);
+        let results = Searcher::for_set(&set)
+            .with_span_options(SpanOptions::new().merge_overlapping(true))
+            .search_reader(LOG.as_bytes())
+            .unwrap();
+        assert_eq!(results.matches[0].spans, vec![7..16]);
+    }
+
+    #[test]
+    fn skips_utf8_boms() {
+        let pattern = MaybeRegex::new("^error");
+        let bom = [b"\xEF\xBB\xBF", "error first\n".as_bytes()].concat();
+        let results = Searcher::new(&pattern).search_reader(&bom[..]).unwrap();
+        assert_eq!(results.matches[0].line, "error first");
+    }
+
+    #[cfg(feature = "encoding")]
+    #[test]
+    fn transcodes_utf16() {
+        let pattern = MaybeRegex::new("^error");
+        let mut utf16 = vec![0xFF, 0xFE];
+        utf16.extend(LOG.encode_utf16().flat_map(u16::to_le_bytes));
+        let results = Searcher::new(&pattern).search_reader(&utf16[..]).unwrap();
+        assert_eq!(results.matches.len(), 2);
+    }
+
+    #[test]
+    fn stops_when_cancelled() {
+        let pattern = MaybeRegex::new("e");
+        let token = CancellationToken::new();
+        token.cancel();
+        let results = Searcher::new(&pattern)
+            .with_cancellation(token)
+            .search_reader(LOG.as_bytes())
+            .unwrap();
+        assert!(results.cancelled);
+        assert!(results.matches.is_empty());
+    }
+
+    #[test]
+    fn reports_progress() {
+        let pattern = MaybeRegex::new("error");
+        let reports = std::sync::Mutex::new(vec![]);
+        let results = Searcher::new(&pattern)
+            .with_progress(Duration::ZERO, |progress| {
+                reports.lock().unwrap().push(*progress)
+            })
+            .search_reader(LOG.as_bytes())
+            .unwrap();
+        assert_eq!(results.matches.len(), 2);
+
+        let reports = reports.into_inner().unwrap();
+        // One per line, plus the final report
+        assert_eq!(reports.len(), 5);
+        assert_eq!(
+            reports.last(),
+            Some(&Progress {
+                bytes_processed: LOG.len() as u64,
+                files_done: 0,
+                matches: 2,
+            })
+        );
+    }
+}
@@ -0,0 +1,443 @@
+use crate::{
+    MatchOutcome, MaybeRegex, MaybeRegexSetBuilder,
+    compile::{CompileReport, CompiledSet, DEFAULT_MEMORY_BUDGET},
+};
+use std::{path::Path, sync::OnceLock};
+
+/// A collection of patterns evaluated together.
+///
+/// A haystack matches the set when it contains at least one of the positive
+/// patterns (or the set has no positive patterns) and none of the negative ones.
+///
+/// Patterns keep their insertion order, including through `dedup` and serialization, so a
+/// pattern's index can be stored and used to refer to it later.
+///
+/// `matches` compiles the patterns into shared automata on first use. If that would exceed
+/// the memory budget, the set falls back to checking each pattern in turn.
+#[derive(Debug, Default, Clone)]
+pub struct MaybeRegexSet {
+    patterns: Vec<MaybeRegex>,
+    // Parallel to `patterns`.
+    weights: Vec<f64>,
+    memory_budget: Option<usize>,
+    compiled: OnceLock<(Option<CompiledSet>, CompileReport)>,
+}
+
+impl PartialEq for MaybeRegexSet {
+    fn eq(&self, other: &Self) -> bool {
+        self.patterns == other.patterns
+            && self.weights == other.weights
+            && self.memory_budget == other.memory_budget
+    }
+}
+
+impl MaybeRegexSet {
+    pub fn new<I, S>(patterns: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        let mut set = Self::default();
+        for pattern in patterns {
+            set.push(MaybeRegex::new(pattern));
+        }
+        set
+    }
+
+    pub fn builder() -> MaybeRegexSetBuilder {
+        MaybeRegexSetBuilder::new()
+    }
+
+    /// Bounds the memory used when compiling the set, for sets built from untrusted input.
+    /// Defaults to `DEFAULT_MEMORY_BUDGET`.
+    pub fn with_memory_budget(mut self, bytes: usize) -> Self {
+        self.memory_budget = Some(bytes);
+        self.compiled = OnceLock::new();
+        self
+    }
+
+    pub fn memory_budget(&self) -> usize {
+        self.memory_budget.unwrap_or(DEFAULT_MEMORY_BUDGET)
+    }
+
+    /// Compiles the set if it hasn't been already and reports what that cost.
+    pub fn compile_report(&self) -> CompileReport {
+        self.compiled().1
+    }
+
+    pub fn push(&mut self, pattern: MaybeRegex) {
+        self.push_weighted(pattern, 1.0);
+    }
+
+    pub fn push_weighted(&mut self, pattern: MaybeRegex, weight: f64) {
+        self.patterns.push(pattern);
+        self.weights.push(weight);
+        self.compiled = OnceLock::new();
+    }
+
+    pub fn weight(&self, index: usize) -> Option<f64> {
+        self.weights.get(index).copied()
+    }
+
+    /// Sums the weights of the positive patterns contained in `haystack`, or 0.0 if the set
+    /// doesn't match it at all.
+    pub fn score<S: AsRef<str>>(&self, haystack: S) -> f64 {
+        let haystack = haystack.as_ref();
+        if !self.matches(haystack) {
+            return 0.0;
+        }
+        self.contained(haystack)
+            .into_iter()
+            .zip(&self.patterns)
+            .zip(&self.weights)
+            .filter(|((contained, pattern), _)| *contained && !pattern.is_negative)
+            .map(|(_, weight)| weight)
+            .sum()
+    }
+
+    pub fn len(&self) -> usize {
+        self.patterns.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.patterns.is_empty()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &MaybeRegex> {
+        self.patterns.iter()
+    }
+
+    pub fn get(&self, index: usize) -> Option<&MaybeRegex> {
+        self.patterns.get(index)
+    }
+
+    /// Removes patterns equal to an earlier one, keeping the first of each. Returns how many
+    /// were removed.
+    pub fn dedup(&mut self) -> usize {
+        let before = self.patterns.len();
+        let mut kept: Vec<(MaybeRegex, f64)> = Vec::with_capacity(before);
+        for (pattern, weight) in self.patterns.drain(..).zip(self.weights.drain(..)) {
+            if !kept.iter().any(|(existing, _)| *existing == pattern) {
+                kept.push((pattern, weight));
+            }
+        }
+        (self.patterns, self.weights) = kept.into_iter().unzip();
+        self.compiled = OnceLock::new();
+        before - self.patterns.len()
+    }
+
+    /// Indices of the patterns, ignoring negation, contained within `haystack`, in ascending
+    /// order.
+    pub fn matching_indices<S: AsRef<str>>(&self, haystack: S) -> Vec<usize> {
+        self.contained(haystack.as_ref())
+            .into_iter()
+            .enumerate()
+            .filter_map(|(index, contained)| contained.then_some(index))
+            .collect()
+    }
+
+    /// Every match of every pattern as `(pattern index, (start, len))`, ordered by position and
+    /// then by pattern index.
+    pub fn match_indices<S: AsRef<str>>(&self, haystack: S) -> Vec<(usize, (usize, usize))> {
+        let haystack = haystack.as_ref();
+        let mut indices: Vec<_> = self
+            .matching_indices(haystack)
+            .into_iter()
+            .flat_map(|index| {
+                self.patterns[index]
+                    .match_indices(haystack)
+                    .into_iter()
+                    .map(move |span| (index, span))
+            })
+            .collect();
+        indices.sort_by_key(|&(index, (start, len))| (start, index, len));
+        indices
+    }
+
+    pub fn matches<S: AsRef<str>>(&self, haystack: S) -> bool {
+        let haystack = haystack.as_ref();
+        match &self.compiled().0 {
+            Some(compiled) => compiled.matches(haystack),
+            None => self.evaluate(|pattern| pattern.is_contained_within(haystack)),
+        }
+    }
+
+    /// Like `matches`, telling apart a haystack no positive pattern was found in from one a
+    /// negative pattern excluded. Exclusion wins when both apply.
+    pub fn match_outcome<S: AsRef<str>>(&self, haystack: S) -> MatchOutcome {
+        let mut has_positive = false;
+        let mut any_positive_matched = false;
+        for (contained, pattern) in self
+            .contained(haystack.as_ref())
+            .into_iter()
+            .zip(&self.patterns)
+        {
+            match (contained, pattern.is_negative) {
+                (true, true) => return MatchOutcome::ExcludedByNegative,
+                (contained, false) => {
+                    has_positive = true;
+                    any_positive_matched |= contained;
+                }
+                (false, true) => {}
+            }
+        }
+        if any_positive_matched || !has_positive {
+            MatchOutcome::Matched
+        } else {
+            MatchOutcome::NotMatched
+        }
+    }
+
+    // Whether each pattern, ignoring negation, is contained within `haystack`.
+    pub(crate) fn contained(&self, haystack: &str) -> Vec<bool> {
+        match &self.compiled().0 {
+            Some(compiled) => compiled.contained(haystack),
+            None => self
+                .patterns
+                .iter()
+                .map(|pattern| pattern.is_contained_within(haystack))
+                .collect(),
+        }
+    }
+
+    fn compiled(&self) -> &(Option<CompiledSet>, CompileReport) {
+        self.compiled
+            .get_or_init(|| CompiledSet::build(&self.patterns, self.memory_budget()))
+    }
+
+    // Each pattern normalizes the path according to its own `PathMatchMode`.
+    pub fn matches_path<P: AsRef<Path>>(&self, path: P) -> bool {
+        let path = path.as_ref();
+        self.evaluate(|pattern| pattern.is_contained_within(pattern.normalize_path(path)))
+    }
+
+    fn evaluate(&self, is_contained: impl Fn(&MaybeRegex) -> bool) -> bool {
+        let mut has_positive = false;
+        let mut any_positive_matched = false;
+        for pattern in &self.patterns {
+            if pattern.is_negative {
+                if is_contained(pattern) {
+                    return false;
+                }
+            } else {
+                has_positive = true;
+                any_positive_matched = any_positive_matched || is_contained(pattern);
+            }
+        }
+        any_positive_matched || !has_positive
+    }
+
+    /// Builds a predicate for walking the directory at `root`, taking each entry's path and
+    /// whether it's a directory. With the `ignore` feature, `filter_walk` applies it to an
+    /// `ignore::WalkBuilder`.
+    ///
+    /// Paths are matched relative to `root`, so walking `/home/u/targets` with `-target` only
+    /// prunes what's below it, and `^src/` matches `./src/lib.rs` when walking `.`.
+    /// Directories are only pruned by negative patterns, so positive patterns like `\.rs$`
+    /// don't stop the walker from descending. Files must match the whole set.
+    pub fn entry_filter<P: AsRef<Path>>(
+        &self,
+        root: P,
+    ) -> impl Fn(&Path, bool) -> bool + Send + Sync + 'static {
+        let set = self.clone();
+        let root = root.as_ref().to_path_buf();
+        move |path, is_dir| set.keeps_entry(path.strip_prefix(&root).unwrap_or(path), is_dir)
+    }
+
+    // Whether a walker should keep the entry at `path`, relative to the walk's root. The root
+    // itself is always kept.
+    pub(crate) fn keeps_entry(&self, path: &Path, is_dir: bool) -> bool {
+        if path.as_os_str().is_empty() {
+            true
+        } else if is_dir {
+            !self.patterns.iter().any(|pattern| {
+                pattern.is_negative && pattern.is_contained_within(pattern.normalize_path(path))
+            })
+        } else {
+            self.matches_path(path)
+        }
+    }
+}
+
+impl FromIterator<MaybeRegex> for MaybeRegexSet {
+    fn from_iter<I: IntoIterator<Item = MaybeRegex>>(iter: I) -> Self {
+        let mut set = Self::default();
+        set.extend(iter);
+        set
+    }
+}
+
+impl<'a> FromIterator<&'a str> for MaybeRegexSet {
+    fn from_iter<I: IntoIterator<Item = &'a str>>(iter: I) -> Self {
+        Self::new(iter)
+    }
+}
+
+// Like `push`, the shared automaton is only rebuilt on the next match.
+impl Extend<MaybeRegex> for MaybeRegexSet {
+    fn extend<I: IntoIterator<Item = MaybeRegex>>(&mut self, iter: I) {
+        for pattern in iter {
+            self.patterns.push(pattern);
+            self.weights.push(1.0);
+        }
+        self.compiled = OnceLock::new();
+    }
+}
+
+impl<'a> Extend<&'a str> for MaybeRegexSet {
+    fn extend<I: IntoIterator<Item = &'a str>>(&mut self, iter: I) {
+        self.extend(iter.into_iter().map(MaybeRegex::new));
+    }
+}
+
+// Serialized as a list in insertion order. Each entry is the pattern text, with its `-` if
+// negative, or `{ "pattern": ..., "weight": ... }` when the weight isn't 1.0. Case sensitivity
+// and other settings aren't persisted.
+#[cfg(feature = "serde")]
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(untagged)]
+enum SerializedPattern {
+    Text(String),
+    Weighted { pattern: String, weight: f64 },
+}
+
+#[cfg(feature = "serde")]
+impl serde::Serialize for MaybeRegexSet {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq(
+            self.patterns
+                .iter()
+                .zip(&self.weights)
+                .map(|(pattern, &weight)| {
+                    let text = pattern.to_string();
+                    if weight == 1.0 {
+                        SerializedPattern::Text(text)
+                    } else {
+                        SerializedPattern::Weighted {
+                            pattern: text,
+                            weight,
+                        }
+                    }
+                }),
+        )
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for MaybeRegexSet {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let mut set = Self::default();
+        for entry in Vec::<SerializedPattern>::deserialize(deserializer)? {
+            match entry {
+                SerializedPattern::Text(text) => set.push(MaybeRegex::new(text)),
+                SerializedPattern::Weighted { pattern, weight } => {
+                    set.push_weighted(MaybeRegex::new(pattern), weight)
+                }
+            }
+        }
+        Ok(set)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn negatives_veto_positives() {
+        let set = MaybeRegexSet::new(["\\.rs$", "-test"]);
+        assert!(set.matches("src/lib.rs"));
+        assert!(!set.matches("src/test.rs"));
+        assert!(!set.matches("README.md"));
+        assert!(MaybeRegexSet::new(["-test"]).matches("README.md"));
+
+        assert_eq!(set.match_outcome("src/lib.rs"), MatchOutcome::Matched);
+        assert_eq!(
+            set.match_outcome("src/test.rs"),
+            MatchOutcome::ExcludedByNegative
+        );
+        assert_eq!(set.match_outcome("README.md"), MatchOutcome::NotMatched);
+    }
+
+    #[test]
+    fn falls_back_when_over_budget() {
+        let patterns = ["error", "^warn(ing)?:", "-ignored", "[0-9]{3}-[a-z]+"];
+        let haystacks = [
+            "error: x",
+            "Warning: y",
+            "error: ignored",
+            "id 123-abc",
+            "info",
+        ];
+
+        let compiled = MaybeRegexSet::new(patterns);
+        let report = compiled.compile_report();
+        assert!(report.compiled);
+        assert!(report.literal_bytes > 0 && report.regex_bytes > 0);
+
+        let limited = MaybeRegexSet::new(patterns).with_memory_budget(64);
+        let report = limited.compile_report();
+        assert!(!report.compiled);
+        assert_eq!(report.budget, 64);
+
+        for haystack in haystacks {
+            assert_eq!(compiled.matches(haystack), limited.matches(haystack));
+        }
+        assert!(compiled.matches("Warning: y"));
+        assert!(!compiled.matches("error: ignored"));
+    }
+
+    #[test]
+    fn collects_and_extends() {
+        let mut set: MaybeRegexSet = ["error", "-ignored"].into_iter().collect();
+        assert!(set.matches("error: x"));
+        assert!(!set.matches("warning: x"));
+
+        set.extend(["^warn"]);
+        assert!(set.matches("warning: x"));
+        set.extend([MaybeRegex::new("-x$")]);
+        assert!(!set.matches("warning: x"));
+        assert_eq!(set.len(), 4);
+
+        let collected: MaybeRegexSet = set.iter().cloned().collect();
+        assert_eq!(collected, set);
+    }
+
+    #[test]
+    fn keeps_insertion_order() {
+        let mut set = MaybeRegexSet::new(["b", "a", "-c", "b", "a"]);
+        assert_eq!(set.dedup(), 2);
+        let order: Vec<_> = set.iter().map(MaybeRegex::to_str).collect();
+        assert_eq!(order, ["b", "a", "c"]);
+        assert!(set.get(2).unwrap().is_negative);
+
+        assert_eq!(set.matching_indices("a b c"), [0, 1, 2]);
+        assert_eq!(
+            set.match_indices("ab ba"),
+            [(1, (0, 1)), (0, (1, 1)), (0, (3, 1)), (1, (4, 1))]
+        );
+    }
+
+    #[cfg(all(feature = "serde", feature = "json"))]
+    #[test]
+    fn serializes_in_order() {
+        let mut set = MaybeRegexSet::new(["z", "-y"]);
+        set.push_weighted(MaybeRegex::new("x"), 2.5);
+        let json = serde_json::to_string(&set).unwrap();
+        assert_eq!(json, r#"["z","-y",{"pattern":"x","weight":2.5}]"#);
+        assert_eq!(serde_json::from_str::<MaybeRegexSet>(&json).unwrap(), set);
+    }
+
+    #[test]
+    fn entry_filter_only_prunes_directories_with_negatives() {
+        let filter = MaybeRegexSet::new(["\\.rs$", "-target"]).entry_filter("/home/u/targets");
+        assert!(filter(Path::new("/home/u/targets/src"), true));
+        assert!(!filter(Path::new("/home/u/targets/target"), true));
+        assert!(filter(Path::new("/home/u/targets/src/lib.rs"), false));
+        assert!(!filter(Path::new("/home/u/targets/src/lib.txt"), false));
+
+        let filter = MaybeRegexSet::new(["^src/"]).entry_filter(".");
+        assert!(filter(Path::new("./src/lib.rs"), false));
+        assert!(!filter(Path::new("./tests/src/lib.rs"), false));
+    }
+}
@@ -0,0 +1,336 @@
+use crate::{FallbackPolicy, MaybeRegex, MaybeRegexSet, NegationMarkers, error::SetBuildError};
+use std::collections::HashMap;
+
+/// Settings for one pattern added to a `MaybeRegexSetBuilder`. Anything left unset falls
+/// back to the builder's defaults.
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct PatternOptions {
+    pub case_sensitive: Option<bool>,
+    pub whole_word: Option<bool>,
+    /// Whether the pattern must match the entire haystack rather than just part of it.
+    pub full_match: Option<bool>,
+    pub weight: Option<f64>,
+    /// Overrides the `-` marker in the pattern text.
+    pub negative: Option<bool>,
+}
+
+impl PatternOptions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn case_sensitive(mut self, case_sensitive: bool) -> Self {
+        self.case_sensitive = Some(case_sensitive);
+        self
+    }
+
+    pub fn whole_word(mut self, whole_word: bool) -> Self {
+        self.whole_word = Some(whole_word);
+        self
+    }
+
+    pub fn full_match(mut self, full_match: bool) -> Self {
+        self.full_match = Some(full_match);
+        self
+    }
+
+    pub fn weight(mut self, weight: f64) -> Self {
+        self.weight = Some(weight);
+        self
+    }
+
+    pub fn negative(mut self, negative: bool) -> Self {
+        self.negative = Some(negative);
+        self
+    }
+}
+
+/// Builds a `MaybeRegexSet` whose patterns each carry their own settings.
+///
+/// ```
+/// use maybe_regex::{MaybeRegexSet, PatternOptions};
+///
+/// let set = MaybeRegexSet::builder()
+///     .whole_word(true)
+///     .pattern("error")
+///     .pattern_with("Fatal", PatternOptions::new().case_sensitive(true).weight(5.0))
+///     .pattern("-ignored")
+///     .build()
+///     .unwrap();
+/// assert!(set.matches("an error occurred"));
+/// assert!(!set.matches("errors occurred"));
+/// assert_eq!(set.score("Fatal error"), 6.0);
+/// ```
+#[derive(Debug, Default, Clone)]
+pub struct MaybeRegexSetBuilder {
+    case_sensitive: bool,
+    smart_case: bool,
+    ascii: bool,
+    whole_word: bool,
+    word_characters: String,
+    full_match: bool,
+    weight: f64,
+    fallback: FallbackPolicy,
+    negation: NegationMarkers,
+    entries: Vec<(String, PatternOptions)>,
+}
+
+impl MaybeRegexSetBuilder {
+    pub fn new() -> Self {
+        Self {
+            weight: 1.0,
+            ..Default::default()
+        }
+    }
+
+    /// The default for patterns that don't set their own.
+    pub fn case_sensitive(mut self, case_sensitive: bool) -> Self {
+        self.case_sensitive = case_sensitive;
+        self
+    }
+
+    /// Makes patterns that don't set their own case sensitivity case sensitive if they have
+    /// an uppercase letter, as with `MaybeRegexBuilder::smart_case`. Overrides the builder's
+    /// `case_sensitive` default.
+    pub fn smart_case(mut self, smart_case: bool) -> Self {
+        self.smart_case = smart_case;
+        self
+    }
+
+    /// Folds only ASCII case and keeps regex classes ASCII-only, as with
+    /// `MaybeRegexBuilder::ascii`.
+    pub fn ascii(mut self, ascii: bool) -> Self {
+        self.ascii = ascii;
+        self
+    }
+
+    /// The default for patterns that don't set their own.
+    pub fn whole_word(mut self, whole_word: bool) -> Self {
+        self.whole_word = whole_word;
+        self
+    }
+
+    /// What counts as part of a word for `whole_word`, as with
+    /// `MaybeRegexBuilder::word_characters`.
+    pub fn word_characters<S: Into<String>>(mut self, characters: S) -> Self {
+        self.word_characters = characters.into();
+        self
+    }
+
+    /// The default for patterns that don't set their own. When on, `matches` means the
+    /// pattern matches the entire haystack, as with `MaybeRegex::matches_exactly`.
+    pub fn full_match(mut self, full_match: bool) -> Self {
+        self.full_match = full_match;
+        self
+    }
+
+    /// The default for patterns that don't set their own. Starts at 1.0.
+    pub fn weight(mut self, weight: f64) -> Self {
+        self.weight = weight;
+        self
+    }
+
+    /// What to do with patterns that look like regexes but don't compile. Defaults to
+    /// `FallbackPolicy::TreatAsLiteral`.
+    pub fn fallback(mut self, fallback: FallbackPolicy) -> Self {
+        self.fallback = fallback;
+        self
+    }
+
+    /// Which markers make patterns negative. Defaults to a leading or trailing `-`; e.g.
+    /// `NegationMarkers::prefix("-")` leaves a trailing `-` in the text.
+    pub fn negation_markers(mut self, markers: NegationMarkers) -> Self {
+        self.negation = markers;
+        self
+    }
+
+    pub fn pattern<S: AsRef<str>>(self, pattern: S) -> Self {
+        self.pattern_with(pattern, PatternOptions::default())
+    }
+
+    pub fn pattern_with<S: AsRef<str>>(mut self, pattern: S, options: PatternOptions) -> Self {
+        self.entries.push((pattern.as_ref().to_string(), options));
+        self
+    }
+
+    /// Builds the set, rejecting duplicate patterns, patterns that are both positive and
+    /// negative, negative or NaN weights, and invalid regexes under `FallbackPolicy::Error`.
+    pub fn build(self) -> Result<MaybeRegexSet, SetBuildError> {
+        let mut set = MaybeRegexSet::default();
+        // Negation of each pattern seen so far, keyed by pattern text.
+        let mut seen = HashMap::new();
+        for (text, options) in self.entries {
+            let smart_case = self.smart_case && options.case_sensitive.is_none();
+            let mut pattern = match MaybeRegex::builder(&text)
+                .smart_case(smart_case)
+                .ascii(self.ascii)
+                .whole_word(options.whole_word.unwrap_or(self.whole_word))
+                .word_characters(self.word_characters.as_str())
+                .negation_markers(self.negation.clone())
+                .fallback(self.fallback)
+                .build_with_fallback()
+            {
+                Ok(Some(pattern)) => pattern,
+                Ok(None) => continue,
+                Err(error) => {
+                    return Err(SetBuildError::InvalidPattern {
+                        pattern: text,
+                        error,
+                    });
+                }
+            };
+            if let Some(negative) = options.negative {
+                pattern.is_negative = negative;
+            }
+            let key = pattern.original.clone();
+            match seen.insert(key.clone(), pattern.is_negative) {
+                Some(negative) if negative == pattern.is_negative => {
+                    return Err(SetBuildError::Duplicate { pattern: key });
+                }
+                Some(_) => return Err(SetBuildError::Conflict { pattern: key }),
+                None => {}
+            }
+
+            let weight = options.weight.unwrap_or(self.weight);
+            if weight.is_nan() || weight < 0.0 {
+                return Err(SetBuildError::InvalidWeight { pattern: key });
+            }
+            if !smart_case && options.case_sensitive.unwrap_or(self.case_sensitive) {
+                pattern = pattern.as_case_sensitive();
+            }
+            if options.full_match.unwrap_or(self.full_match) {
+                pattern = pattern.as_full_match();
+            }
+            set.push_weighted(pattern, weight);
+        }
+        Ok(set)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn builds_ascii_sets() {
+        let set = MaybeRegexSet::builder()
+            .ascii(true)
+            .pattern("Error")
+            .pattern("^\\w+$")
+            .pattern("-école")
+            .build()
+            .unwrap();
+        assert!(set.compile_report().compiled);
+        assert!(set.matches("ERROR: x") && set.matches("Word"));
+        assert!(!set.matches("héllo") && !set.matches("error at école"));
+        assert!(set.matches("error at ÉCOLE"));
+
+        // Case-sensitive patterns are ASCII-only too
+        let set = MaybeRegexSet::builder()
+            .ascii(true)
+            .smart_case(true)
+            .pattern("^Caf\\w$")
+            .build()
+            .unwrap();
+        let pattern = MaybeRegex::builder("^Caf\\w$")
+            .ascii(true)
+            .smart_case(true)
+            .build();
+        assert!(set.compile_report().compiled);
+        for haystack in ["Cafe", "Café", "cafe"] {
+            assert_eq!(
+                set.matches(haystack),
+                pattern.matches(haystack),
+                "{haystack}"
+            );
+        }
+        assert!(set.matches("Cafe") && !set.matches("Café"));
+    }
+
+    #[test]
+    fn applies_overrides_and_validates() {
+        let set = MaybeRegexSet::builder()
+            .weight(2.0)
+            .pattern("cat")
+            .pattern_with("dog", PatternOptions::new().whole_word(true).weight(0.5))
+            .pattern_with("bird", PatternOptions::new().negative(true))
+            .build()
+            .unwrap();
+        assert_eq!(set.len(), 3);
+        assert_eq!(set.weight(0), Some(2.0));
+        assert!(set.matches("concatenate"));
+        assert!(!set.matches("hotdogs"));
+        assert!(!set.matches("cat and bird"));
+        assert_eq!(set.score("cat and dog"), 2.5);
+        assert_eq!(set.score("cat and bird"), 0.0);
+
+        let exact = MaybeRegexSet::builder()
+            .full_match(true)
+            .pattern("yes")
+            .pattern("[0-9]+")
+            .pattern_with("maybe", PatternOptions::new().full_match(false))
+            .build()
+            .unwrap();
+        assert!(exact.matches("YES"));
+        assert!(exact.matches("42"));
+        assert!(!exact.matches("yes please"));
+        assert!(!exact.matches("a42"));
+        assert!(exact.matches("well, maybe"));
+
+        let ranges = MaybeRegexSet::builder()
+            .negation_markers(NegationMarkers::prefix("-"))
+            .pattern("2021-")
+            .pattern("-draft")
+            .build()
+            .unwrap();
+        assert!(ranges.matches("2021-05 final") && !ranges.matches("2021-05 draft"));
+
+        let smart = MaybeRegexSet::builder()
+            .smart_case(true)
+            .pattern("Fatal")
+            .pattern_with("Warn", PatternOptions::new().case_sensitive(false))
+            .pattern("-debug")
+            .build()
+            .unwrap();
+        assert!(smart.matches("Fatal") && !smart.matches("FATAL"));
+        assert!(smart.matches("WARN") && !smart.matches("Fatal DEBUG"));
+
+        let duplicate = MaybeRegexSet::builder()
+            .pattern("cat")
+            .pattern("cat")
+            .build();
+        assert_eq!(
+            duplicate,
+            Err(SetBuildError::Duplicate {
+                pattern: "cat".into()
+            })
+        );
+        let conflict = MaybeRegexSet::builder()
+            .pattern("cat")
+            .pattern("-cat")
+            .build();
+        assert_eq!(
+            conflict,
+            Err(SetBuildError::Conflict {
+                pattern: "cat".into()
+            })
+        );
+        let strict = MaybeRegexSet::builder()
+            .fallback(FallbackPolicy::Error)
+            .pattern("[unclosed")
+            .build();
+        assert!(matches!(strict, Err(SetBuildError::InvalidPattern { .. })));
+        let lenient = MaybeRegexSet::builder()
+            .fallback(FallbackPolicy::Skip)
+            .pattern("[unclosed")
+            .pattern("ok")
+            .build()
+            .unwrap();
+        assert_eq!(lenient.len(), 1);
+
+        let weight = MaybeRegexSet::builder()
+            .pattern_with("cat", PatternOptions::new().weight(-1.0))
+            .build();
+        assert!(matches!(weight, Err(SetBuildError::InvalidWeight { .. })));
+    }
+}
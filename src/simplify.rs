@@ -0,0 +1,289 @@
+use crate::{MaybeRegex, TagWrapperData, build_regex};
+use regex_syntax::ast::{
+    self, Ast, ClassSet, ClassSetItem, Flag, Flags, FlagsItemKind, GroupKind, RepetitionKind,
+    RepetitionRange,
+};
+
+impl MaybeRegex {
+    /// Rewrites the pattern into an equivalent but tidier form: redundant groups are removed,
+    /// nested alternations are flattened and deduplicated, repetitions like `{0,1}` become `?`,
+    /// and simple bracketed classes are sorted and merged.
+    ///
+    /// Literal patterns, and regexes that can't be parsed, are returned unchanged.
+    pub fn simplify(&self) -> Self {
+        let TagWrapperData::Regex(regex) = &self.data else {
+            return self.clone();
+        };
+        let Ok(parsed) = ast::parse::Parser::new().parse(regex.as_str()) else {
+            return self.clone();
+        };
+
+        let simplified = Printer::default().alternation(&parsed);
+        if simplified == regex.as_str() {
+            return self.clone();
+        }
+        match build_regex(&simplified, !self.case_sensitive) {
+            Some(regex) => Self {
+                data: TagWrapperData::Regex(regex),
+                original: simplified,
+                ..self.clone()
+            },
+            None => self.clone(),
+        }
+    }
+}
+
+#[derive(Default)]
+struct Printer {
+    output: String,
+}
+
+impl Printer {
+    fn alternation(mut self, ast: &Ast) -> String {
+        self.print_alternation(ast);
+        self.output
+    }
+
+    fn print_alternation(&mut self, ast: &Ast) {
+        match transparent(ast) {
+            Ast::Alternation(alternation) => {
+                let mut branches: Vec<String> = vec![];
+                collect_branches(&alternation.asts, &mut branches);
+                self.output.push_str(&branches.join("|"));
+            }
+            ast => self.print_concat(ast),
+        }
+    }
+
+    fn print_concat(&mut self, ast: &Ast) {
+        match transparent(ast) {
+            Ast::Alternation(_) => self.wrap(ast, Self::print_alternation),
+            Ast::Concat(concat) => {
+                for ast in &concat.asts {
+                    self.print_concat(ast);
+                }
+            }
+            Ast::Empty(_) => {}
+            ast => self.print_atom(ast),
+        }
+    }
+
+    fn print_atom(&mut self, ast: &Ast) {
+        match transparent(ast) {
+            Ast::Alternation(_) | Ast::Concat(_) => self.wrap(ast, Self::print_alternation),
+            // Only reached when something applies to it, like the `+` in `(?:)+`
+            Ast::Empty(_) => self.output.push_str("(?:)"),
+            Ast::Repetition(repetition) => {
+                let suffix = match &repetition.op.kind {
+                    RepetitionKind::Range(RepetitionRange::Exactly(1)) => {
+                        return self.print_atom(&repetition.ast);
+                    }
+                    RepetitionKind::ZeroOrOne
+                    | RepetitionKind::Range(RepetitionRange::Bounded(0, 1)) => "?".into(),
+                    RepetitionKind::ZeroOrMore
+                    | RepetitionKind::Range(RepetitionRange::AtLeast(0)) => "*".into(),
+                    RepetitionKind::OneOrMore
+                    | RepetitionKind::Range(RepetitionRange::AtLeast(1)) => "+".into(),
+                    RepetitionKind::Range(RepetitionRange::Exactly(n)) => format!("{{{n}}}"),
+                    RepetitionKind::Range(RepetitionRange::AtLeast(n)) => format!("{{{n},}}"),
+                    RepetitionKind::Range(RepetitionRange::Bounded(min, max)) => {
+                        format!("{{{min},{max}}}")
+                    }
+                };
+                // Stacked repetitions like `a**` aren't valid, so the inner one needs a group
+                if matches!(transparent(&repetition.ast), Ast::Repetition(_)) {
+                    self.wrap(&repetition.ast, Self::print_alternation);
+                } else {
+                    self.print_atom(&repetition.ast);
+                }
+                self.output.push_str(&suffix);
+                if !repetition.greedy {
+                    self.output.push('?');
+                }
+            }
+            Ast::Group(group) => {
+                match &group.kind {
+                    GroupKind::CaptureIndex(_) => self.output.push('('),
+                    GroupKind::CaptureName { name, .. } => {
+                        self.output.push_str(&format!("(?P<{}>", name.name))
+                    }
+                    GroupKind::NonCapturing(flags) => self
+                        .output
+                        .push_str(&format!("(?{}:", flags_to_string(flags))),
+                }
+                self.print_alternation(&group.ast);
+                self.output.push(')');
+            }
+            Ast::ClassBracketed(class) => match simple_ranges(&class.kind) {
+                Some(ranges) => {
+                    self.output.push('[');
+                    if class.negated {
+                        self.output.push('^');
+                    }
+                    for (start, end) in ranges {
+                        push_class_char(&mut self.output, start);
+                        if end > start {
+                            if end as u32 > start as u32 + 1 {
+                                self.output.push('-');
+                            }
+                            push_class_char(&mut self.output, end);
+                        }
+                    }
+                    self.output.push(']');
+                }
+                None => self.print_leaf(ast),
+            },
+            ast => self.print_leaf(ast),
+        }
+    }
+
+    fn print_leaf(&mut self, ast: &Ast) {
+        // Printing into a String can't fail
+        let _ = ast::print::Printer::new().print(ast, &mut self.output);
+    }
+
+    fn wrap(&mut self, ast: &Ast, print: fn(&mut Self, &Ast)) {
+        self.output.push_str("(?:");
+        print(self, ast);
+        self.output.push(')');
+    }
+}
+
+// Non-capturing groups without flags don't change the meaning of what they contain,
+// so they're looked through and only re-added where precedence requires it.
+fn transparent(ast: &Ast) -> &Ast {
+    match ast {
+        Ast::Group(group) => match &group.kind {
+            GroupKind::NonCapturing(flags) if flags.items.is_empty() => transparent(&group.ast),
+            _ => ast,
+        },
+        ast => ast,
+    }
+}
+
+fn collect_branches(asts: &[Ast], branches: &mut Vec<String>) {
+    for ast in asts {
+        match transparent(ast) {
+            Ast::Alternation(alternation) => collect_branches(&alternation.asts, branches),
+            ast => {
+                let mut printer = Printer::default();
+                printer.print_concat(ast);
+                // Dropping a branch with groups would renumber the groups after it
+                if has_capture(ast) || !branches.contains(&printer.output) {
+                    branches.push(printer.output);
+                }
+            }
+        }
+    }
+}
+
+fn has_capture(ast: &Ast) -> bool {
+    match ast {
+        Ast::Group(group) => group.capture_index().is_some() || has_capture(&group.ast),
+        Ast::Repetition(repetition) => has_capture(&repetition.ast),
+        Ast::Concat(concat) => concat.asts.iter().any(has_capture),
+        Ast::Alternation(alternation) => alternation.asts.iter().any(has_capture),
+        _ => false,
+    }
+}
+
+fn flags_to_string(flags: &Flags) -> String {
+    flags
+        .items
+        .iter()
+        .map(|item| match item.kind {
+            FlagsItemKind::Negation => '-',
+            FlagsItemKind::Flag(Flag::CaseInsensitive) => 'i',
+            FlagsItemKind::Flag(Flag::MultiLine) => 'm',
+            FlagsItemKind::Flag(Flag::DotMatchesNewLine) => 's',
+            FlagsItemKind::Flag(Flag::SwapGreed) => 'U',
+            FlagsItemKind::Flag(Flag::Unicode) => 'u',
+            FlagsItemKind::Flag(Flag::CRLF) => 'R',
+            FlagsItemKind::Flag(Flag::IgnoreWhitespace) => 'x',
+        })
+        .collect()
+}
+
+// Returns the sorted, merged ranges of a class made only of literals and ranges.
+fn simple_ranges(set: &ClassSet) -> Option<Vec<(char, char)>> {
+    let ClassSet::Item(item) = set else {
+        return None;
+    };
+    let items = match item {
+        ClassSetItem::Union(union) => union.items.iter().collect(),
+        item => vec![item],
+    };
+
+    let mut ranges = vec![];
+    for item in items {
+        match item {
+            ClassSetItem::Literal(literal) => ranges.push((literal.c, literal.c)),
+            ClassSetItem::Range(range) => ranges.push((range.start.c, range.end.c)),
+            _ => return None,
+        }
+    }
+    ranges.sort();
+
+    let mut merged: Vec<(char, char)> = vec![];
+    for (start, end) in ranges {
+        match merged.last_mut() {
+            Some(last) if start as u32 <= last.1 as u32 + 1 => last.1 = last.1.max(end),
+            _ => merged.push((start, end)),
+        }
+    }
+    Some(merged)
+}
+
+fn push_class_char(output: &mut String, c: char) {
+    if regex_syntax::is_meta_character(c) {
+        output.push('\\');
+    }
+    output.push(c);
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn removes_redundancy() {
+        let simplify = |s: &str| MaybeRegex::new(s).simplify().to_str().to_string();
+        assert_eq!(simplify("(?:foo).*"), "foo.*");
+        assert_eq!(simplify("(?:(?:a|b)|c|a)+"), "(?:a|b|c)+");
+        assert_eq!(simplify("x{0,1}y{1}z{1,}$"), "x?yz+$");
+        assert_eq!(simplify("[c-f0-9a-d]+"), "[0-9a-f]+");
+        assert_eq!(simplify("(?P<id>[0-9])+"), "(?P<id>[0-9])+");
+    }
+
+    #[test]
+    fn preserves_behavior() {
+        let original = MaybeRegex::new("^(?:(?:error)|(?:warn)):\\s+");
+        let simplified = original.simplify();
+        assert_eq!(simplified.to_str(), "^(?:error|warn):\\s+");
+        for haystack in ["error:  disk", "warn: x", "info: y", "error"] {
+            assert_eq!(original.matches(haystack), simplified.matches(haystack));
+        }
+    }
+
+    #[test]
+    fn handles_empty_branches_and_groups() {
+        let simplify = |s: &str| MaybeRegex::new(s).simplify().to_str().to_string();
+        assert_eq!(simplify("a|"), "a|");
+        assert_eq!(simplify("x()"), "x()");
+        assert_eq!(simplify("(?:a|)+b"), "(?:a|)+b");
+        assert_eq!(simplify("(?:)+b"), "(?:)+b");
+        assert_eq!(simplify("x(?:)"), "x");
+        assert!(MaybeRegex::new("(?:a|)+b").simplify().matches("b"));
+    }
+
+    #[test]
+    fn keeps_branches_with_groups() {
+        let simplify = |s: &str| MaybeRegex::new(s).simplify().to_str().to_string();
+        assert_eq!(simplify("(a)|(a)|b|b"), "(a)|(a)|b");
+        let captures = MaybeRegex::new("(a)|(a)|(b)")
+            .simplify()
+            .captures("b")
+            .unwrap();
+        assert_eq!(captures.get(3).map(|group| group.as_str()), Some("b"));
+    }
+}
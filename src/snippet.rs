@@ -0,0 +1,91 @@
+use crate::MaybeRegex;
+use std::ops::Range;
+
+/// A window of a document for previewing a search result.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Snippet<'h> {
+    pub text: &'h str,
+    /// Where `text` sits in the document, in bytes.
+    pub range: Range<usize>,
+    /// Match spans relative to `text`, clipped to the window.
+    pub spans: Vec<Range<usize>>,
+}
+
+impl MaybeRegex {
+    /// The window of at most `max_len` bytes holding the most matches, padded evenly with the
+    /// surrounding context. `None` when nothing matches; negative patterns have no matches to
+    /// show, so they always return `None`.
+    pub fn best_snippet<'h>(&self, text: &'h str, max_len: usize) -> Option<Snippet<'h>> {
+        if self.is_negative || max_len == 0 {
+            return None;
+        }
+        let spans = self.spans(text);
+
+        // Densest run of spans that fits in the window
+        let mut best = None;
+        let mut end = 0;
+        for start in 0..spans.len() {
+            end = end.max(start + 1);
+            while end < spans.len() && spans[end].end - spans[start].start <= max_len {
+                end += 1;
+            }
+            if best.is_none_or(|(s, e)| end - start > e - s) {
+                best = Some((start, end));
+            }
+        }
+        let (first, last) = best?;
+
+        let covered = spans[first].start..spans[last - 1].end.min(spans[first].start + max_len);
+        let padding = max_len.saturating_sub(covered.len());
+        let mut start = covered.start.saturating_sub(padding / 2);
+        let mut end = (start + max_len).min(text.len());
+        start = end.saturating_sub(max_len).min(start);
+        while !text.is_char_boundary(start) {
+            start += 1;
+        }
+        while !text.is_char_boundary(end) {
+            end -= 1;
+        }
+
+        Some(Snippet {
+            text: &text[start..end],
+            range: start..end,
+            spans: spans
+                .into_iter()
+                .filter(|span| span.start < end && span.end > start)
+                .map(|span| span.start.max(start) - start..span.end.min(end) - start)
+                .collect(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn picks_densest_window() {
+        let text = concat!(
+            "rust is mentioned once here. ",
+            "Later on we get to the good part: rust, rust and more rust. The end."
+        );
+        let snippet = MaybeRegex::new("rust").best_snippet(text, 30).unwrap();
+        assert_eq!(snippet.spans.len(), 3);
+        assert!(snippet.text.len() <= 30);
+        for span in &snippet.spans {
+            assert_eq!(&snippet.text[span.clone()], "rust");
+        }
+        assert_eq!(&text[snippet.range.clone()], snippet.text);
+
+        assert!(MaybeRegex::new("python").best_snippet(text, 30).is_none());
+        assert!(MaybeRegex::new("-python").best_snippet(text, 30).is_none());
+    }
+
+    #[test]
+    fn clips_long_matches_and_respects_char_boundaries() {
+        let snippet = MaybeRegex::new("b+").best_snippet("ééabbbbbbé", 5).unwrap();
+        assert!(snippet.text.len() <= 5);
+        assert_eq!(snippet.text, "bbbbb");
+        assert_eq!(snippet.spans, vec![0..5]);
+    }
+}
@@ -0,0 +1,104 @@
+use crate::MaybeRegex;
+use std::ops::Range;
+
+/// Post-processing for highlight spans, so every consumer renders the same highlights.
+///
+/// Applied in order: spans shorter than `min_len` are dropped, then overlapping (and, with
+/// `merge_adjacent`, touching) spans are merged, then only the first `max_count` are kept.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SpanOptions {
+    pub merge_overlapping: bool,
+    pub merge_adjacent: bool,
+    pub min_len: usize,
+    pub max_count: Option<usize>,
+}
+
+impl SpanOptions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn merge_overlapping(mut self, merge: bool) -> Self {
+        self.merge_overlapping = merge;
+        self
+    }
+
+    /// Also merges spans that touch, e.g. `0..3` and `3..5`. Implies `merge_overlapping`.
+    pub fn merge_adjacent(mut self, merge: bool) -> Self {
+        self.merge_adjacent = merge;
+        self
+    }
+
+    pub fn min_len(mut self, min_len: usize) -> Self {
+        self.min_len = min_len;
+        self
+    }
+
+    pub fn max_count(mut self, max_count: usize) -> Self {
+        self.max_count = Some(max_count);
+        self
+    }
+
+    pub fn apply(&self, mut spans: Vec<Range<usize>>) -> Vec<Range<usize>> {
+        spans.retain(|span| span.len() >= self.min_len);
+        spans.sort_by_key(|span| (span.start, span.end));
+
+        if self.merge_overlapping || self.merge_adjacent {
+            let mut merged: Vec<Range<usize>> = Vec::with_capacity(spans.len());
+            for span in spans {
+                match merged.last_mut() {
+                    Some(last)
+                        if span.start < last.end
+                            || (self.merge_adjacent && span.start == last.end) =>
+                    {
+                        last.end = last.end.max(span.end);
+                    }
+                    _ => merged.push(span),
+                }
+            }
+            spans = merged;
+        }
+
+        if let Some(max_count) = self.max_count {
+            spans.truncate(max_count);
+        }
+        spans
+    }
+}
+
+impl MaybeRegex {
+    /// Match spans in `haystack`, post-processed by `options`.
+    pub fn highlight_spans(&self, haystack: &str, options: &SpanOptions) -> Vec<Range<usize>> {
+        options.apply(self.spans(haystack))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn post_processes_spans() {
+        let spans = vec![5..6, 0..3, 2..4, 4..5, 10..14];
+        assert_eq!(
+            SpanOptions::new()
+                .merge_overlapping(true)
+                .apply(spans.clone()),
+            vec![0..4, 4..5, 5..6, 10..14]
+        );
+        assert_eq!(
+            SpanOptions::new().merge_adjacent(true).apply(spans.clone()),
+            vec![0..6, 10..14]
+        );
+        assert_eq!(
+            SpanOptions::new().min_len(2).max_count(2).apply(spans),
+            vec![0..3, 2..4]
+        );
+
+        let options = SpanOptions::new().merge_adjacent(true).min_len(2);
+        assert_eq!(
+            MaybeRegex::new("[a-z]+").highlight_spans("ab c de-fg", &options),
+            vec![0..2, 5..7, 8..10]
+        );
+    }
+}
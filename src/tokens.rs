@@ -0,0 +1,214 @@
+use crate::{MaybeRegex, TagWrapperData};
+use std::ops::Range;
+
+/// What a piece of the user's input means, for colorizing it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum TokenKind {
+    /// Text matched as-is. Consecutive literal characters form one token.
+    Literal,
+    /// An escaped character such as `\.`, matched literally.
+    Escape,
+    /// `[...]` or a shorthand like `\d` or `\p{Greek}`.
+    Class,
+    /// `^`, `$`, `\b` and the like.
+    Anchor,
+    /// `.`
+    Wildcard,
+    /// `*`, `+`, `?` or `{n,m}`, including a trailing `?` for laziness.
+    Quantifier,
+    /// An opening parenthesis along with any `?:`, `?P<name>` or flags, or a closing one.
+    Group,
+    Alternation,
+    /// The `-` that makes the pattern negative.
+    Negation,
+}
+
+/// A piece of the user's input and how it was interpreted.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct PatternToken {
+    pub kind: TokenKind,
+    /// Byte range in the input the pattern was created from, including any negation marker.
+    pub span: Range<usize>,
+}
+
+impl MaybeRegex {
+    /// Splits the input this pattern was created from into tokens, so a search box can show
+    /// how it was interpreted. Input that wasn't treated as a regex is a single `Literal`.
+    pub fn pattern_tokens(&self) -> Vec<PatternToken> {
+        let (negation, escape, suffix) = self.markers();
+        let mut tokens = vec![];
+        if negation {
+            tokens.push(PatternToken {
+                kind: TokenKind::Negation,
+                span: 0..1,
+            });
+        }
+        let prefix = usize::from(negation) + usize::from(escape);
+        if escape {
+            tokens.push(PatternToken {
+                kind: TokenKind::Escape,
+                span: prefix - 1..prefix,
+            });
+        }
+
+        let pattern = self.original.as_str();
+        match &self.data {
+            TagWrapperData::Regex(_) => tokens.extend(tokenize(pattern).into_iter().map(
+                |(kind, span)| PatternToken {
+                    kind,
+                    span: span.start + prefix..span.end + prefix,
+                },
+            )),
+            TagWrapperData::Raw(_) if !pattern.is_empty() => tokens.push(PatternToken {
+                kind: TokenKind::Literal,
+                span: prefix..prefix + pattern.len(),
+            }),
+            TagWrapperData::Raw(_) => {}
+        }
+
+        if suffix {
+            let end = prefix + pattern.len();
+            tokens.push(PatternToken {
+                kind: TokenKind::Negation,
+                span: end..end + 1,
+            });
+        }
+        tokens
+    }
+}
+
+fn tokenize(pattern: &str) -> Vec<(TokenKind, Range<usize>)> {
+    let mut tokens: Vec<(TokenKind, Range<usize>)> = vec![];
+    let mut chars = pattern.char_indices().peekable();
+    while let Some((start, c)) = chars.next() {
+        let next_is = |chars: &mut std::iter::Peekable<std::str::CharIndices>, wanted: char| {
+            chars.next_if(|&(_, c)| c == wanted).is_some()
+        };
+        let kind = match c {
+            '\\' => match chars.next().map(|(_, c)| c) {
+                Some('d' | 'D' | 'w' | 'W' | 's' | 'S') => TokenKind::Class,
+                Some('p' | 'P') => {
+                    if next_is(&mut chars, '{') {
+                        while chars.next().is_some_and(|(_, c)| c != '}') {}
+                    } else {
+                        chars.next();
+                    }
+                    TokenKind::Class
+                }
+                Some('b' | 'B' | 'A' | 'z') => TokenKind::Anchor,
+                _ => TokenKind::Escape,
+            },
+            '[' => {
+                // Classes can nest, e.g. `[[:alpha:]_]`, and a `]` straight after the opening
+                // bracket (or its `^`) is literal.
+                let mut depth = 1;
+                next_is(&mut chars, '^');
+                next_is(&mut chars, ']');
+                while depth > 0 {
+                    match chars.next() {
+                        Some((_, '\\')) => {
+                            chars.next();
+                        }
+                        Some((_, '[')) => depth += 1,
+                        Some((_, ']')) => depth -= 1,
+                        Some(_) => {}
+                        None => break,
+                    }
+                }
+                TokenKind::Class
+            }
+            '^' | '$' => TokenKind::Anchor,
+            '.' => TokenKind::Wildcard,
+            '*' | '+' | '?' => {
+                next_is(&mut chars, '?');
+                TokenKind::Quantifier
+            }
+            '{' => match repetition_len(&pattern[start..]) {
+                Some(len) => {
+                    while chars.next_if(|&(i, _)| i < start + len).is_some() {}
+                    next_is(&mut chars, '?');
+                    TokenKind::Quantifier
+                }
+                None => TokenKind::Literal,
+            },
+            '(' => {
+                if next_is(&mut chars, '?') {
+                    for (_, c) in chars.by_ref() {
+                        if matches!(c, ':' | ')' | '>') {
+                            break;
+                        }
+                    }
+                }
+                TokenKind::Group
+            }
+            ')' => TokenKind::Group,
+            '|' => TokenKind::Alternation,
+            _ => TokenKind::Literal,
+        };
+
+        let end = chars.peek().map_or(pattern.len(), |&(i, _)| i);
+        match tokens.last_mut() {
+            Some((TokenKind::Literal, span)) if kind == TokenKind::Literal => span.end = end,
+            _ => tokens.push((kind, start..end)),
+        }
+    }
+    tokens
+}
+
+// Length of a `{n}`, `{n,}` or `{n,m}` repetition at the start of `s`.
+fn repetition_len(s: &str) -> Option<usize> {
+    let end = s.find('}')?;
+    let inner = &s[1..end];
+    let (min, max) = inner.split_once(',').unwrap_or((inner, "0"));
+    let is_number = |s: &str| !s.is_empty() && s.bytes().all(|b| b.is_ascii_digit());
+    (is_number(min) && (max.is_empty() || is_number(max))).then_some(end + 1)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn kinds(input: &str) -> Vec<(TokenKind, &str)> {
+        MaybeRegex::new(input)
+            .pattern_tokens()
+            .into_iter()
+            .map(|token| (token.kind, &input[token.span]))
+            .collect()
+    }
+
+    #[test]
+    fn tokenizes_input() {
+        use TokenKind::*;
+        assert_eq!(
+            kinds("-^err(?:or|) \\d{2,}?\\.[^]a-z]+$"),
+            [
+                (Negation, "-"),
+                (Anchor, "^"),
+                (Literal, "err"),
+                (Group, "(?:"),
+                (Literal, "or"),
+                (Alternation, "|"),
+                (Group, ")"),
+                (Literal, " "),
+                (Class, "\\d"),
+                (Quantifier, "{2,}?"),
+                (Escape, "\\."),
+                (Class, "[^]a-z]"),
+                (Quantifier, "+"),
+                (Anchor, "$"),
+            ]
+        );
+        assert_eq!(
+            kinds("a.b\\p{Greek}-"),
+            [
+                (Literal, "a"),
+                (Wildcard, "."),
+                (Literal, "b"),
+                (Class, "\\p{Greek}"),
+                (Negation, "-"),
+            ]
+        );
+        assert_eq!(kinds("plain text"), [(Literal, "plain text")]);
+        assert_eq!(kinds("-"), [(Negation, "-")]);
+    }
+}
@@ -1,3 +1,5 @@
+use std::ops::Range;
+
 pub fn remove_first_n_chars(s: &str, n: usize) -> String {
     s.chars().skip(n).collect()
 }
@@ -9,3 +11,44 @@ pub fn remove_last_n_chars(s: &str, n: usize) -> String {
     }
     chars.iter().collect()
 }
+
+// Whether `\b` holds at `index`: a word character on exactly one side of it. Word characters
+// are letters, digits, `_` and any in `extra`.
+pub(crate) fn is_word_boundary(haystack: &str, index: usize, extra: &str) -> bool {
+    let is_word = |c: char| c.is_alphanumeric() || c == '_' || extra.contains(c);
+    let before = haystack[..index].chars().next_back().is_some_and(is_word);
+    let after = haystack[index..].chars().next().is_some_and(is_word);
+    before != after
+}
+
+// The matches `find` gives from each offset that have a word boundary at either end, like
+// `\b(?:pattern)\b`, without overlapping. A hit without boundaries doesn't hide one that
+// overlaps it, e.g. `ab ab` in `xab ab ab`.
+pub(crate) fn whole_word_matches<'a>(
+    haystack: &'a str,
+    extra: &'a str,
+    mut find: impl FnMut(usize) -> Option<Range<usize>> + 'a,
+) -> impl Iterator<Item = Range<usize>> + 'a {
+    let mut from = 0;
+    std::iter::from_fn(move || {
+        while from <= haystack.len() {
+            let found = find(from)?;
+            let next_char = haystack[found.start..]
+                .chars()
+                .next()
+                .map_or(1, char::len_utf8);
+            if is_word_boundary(haystack, found.start, extra)
+                && is_word_boundary(haystack, found.end, extra)
+            {
+                from = if found.is_empty() {
+                    found.end + next_char
+                } else {
+                    found.end
+                };
+                return Some(found);
+            }
+            from = found.start + next_char;
+        }
+        None
+    })
+}
@@ -0,0 +1,74 @@
+use crate::MaybeRegexSet;
+use ignore::WalkBuilder;
+use std::path::PathBuf;
+
+impl MaybeRegexSet {
+    /// Makes `builder` skip what this set filters out, like `entry_filter`, so a file walker
+    /// honors the same user filters as content search. Paths are matched relative to the root
+    /// each entry was found under. Directories are only pruned by negative patterns, and files
+    /// must match the whole set.
+    ///
+    /// ```no_run
+    /// use ignore::WalkBuilder;
+    /// use maybe_regex::MaybeRegexSet;
+    ///
+    /// let set = MaybeRegexSet::new(["\\.rs$", "-target"]);
+    /// let mut builder = WalkBuilder::new(".");
+    /// for entry in set.filter_walk(&mut builder).build() {
+    ///     println!("{}", entry.unwrap().path().display());
+    /// }
+    /// ```
+    pub fn filter_walk<'b>(&self, builder: &'b mut WalkBuilder) -> &'b mut WalkBuilder {
+        let set = self.clone();
+        builder.filter_entry(move |entry| {
+            let is_dir = entry
+                .file_type()
+                .is_some_and(|file_type| file_type.is_dir());
+            // The walker doesn't say which root an entry came from, but it's the part of the
+            // path above the entry's depth
+            let components: Vec<_> = entry.path().components().collect();
+            let relative: PathBuf = components[components.len().saturating_sub(entry.depth())..]
+                .iter()
+                .collect();
+            set.keeps_entry(&relative, is_dir)
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::fs;
+
+    #[test]
+    fn filters_walked_entries() {
+        // Matched relative to the root, so its name doesn't count as `target`
+        let root = std::env::temp_dir().join(format!("maybe-regex-targets-{}", std::process::id()));
+        for dir in ["src", "target"] {
+            fs::create_dir_all(root.join(dir)).unwrap();
+        }
+        for file in ["src/lib.rs", "src/notes.txt", "target/build.rs"] {
+            fs::write(root.join(file), "").unwrap();
+        }
+
+        let walk = |patterns: &[&str]| {
+            let mut builder = WalkBuilder::new(&root);
+            let mut found: Vec<_> = MaybeRegexSet::new(patterns)
+                .filter_walk(builder.standard_filters(false))
+                .build()
+                .map(|entry| entry.unwrap().into_path())
+                .filter(|path| path.is_file())
+                .collect();
+            found.sort();
+            found
+        };
+        let rust = walk(&["\\.rs$", "-target"]);
+        let sources = walk(&["^src/"]);
+        fs::remove_dir_all(&root).unwrap();
+        assert_eq!(rust, [root.join("src/lib.rs")]);
+        assert_eq!(
+            sources,
+            [root.join("src/lib.rs"), root.join("src/notes.txt")]
+        );
+    }
+}
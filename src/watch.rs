@@ -0,0 +1,127 @@
+use crate::MaybeRegexSet;
+use log::error;
+use notify::{Event, RecommendedWatcher, RecursiveMode, Watcher};
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+    sync::{Arc, RwLock},
+};
+
+/// A `MaybeRegexSet` loaded from a file (one pattern per line, blank lines and `#` comments
+/// ignored) that can be swapped for a freshly compiled set whenever the file changes.
+///
+/// Readers call `load()` to get the current set; the swap is atomic, so a reader sees either
+/// the old set or the new one, never a partially loaded one.
+pub struct WatchedPatternSet {
+    path: PathBuf,
+    current: Arc<RwLock<Arc<MaybeRegexSet>>>,
+    // Stops watching when dropped
+    watcher: Option<RecommendedWatcher>,
+}
+
+impl WatchedPatternSet {
+    /// Loads the file once. Call `reload` to pick up changes manually.
+    pub fn open<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        let path = path.as_ref().to_path_buf();
+        let set = read_set(&path)?;
+        Ok(Self {
+            path,
+            current: Arc::new(RwLock::new(Arc::new(set))),
+            watcher: None,
+        })
+    }
+
+    /// Loads the file and reloads it whenever the OS reports a change to it, using `notify`.
+    /// The directory holding the file is watched, so editors that save by replacing the file
+    /// are noticed too. If the file becomes unreadable the previous set stays in use.
+    pub fn watch<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        let mut watched = Self::open(path)?;
+        let path = fs::canonicalize(&watched.path)?;
+        let directory = path.parent().unwrap_or(&path).to_path_buf();
+        let current = watched.current.clone();
+
+        let mut watcher =
+            notify::recommended_watcher(move |event: notify::Result<Event>| match event {
+                Ok(event) if event.paths.contains(&path) => match read_set(&path) {
+                    Ok(set) => swap(&current, set),
+                    Err(e) => error!("Failed to reload {}: {e}", path.display()),
+                },
+                Ok(_) => {}
+                Err(e) => error!("Failed to watch {}: {e}", path.display()),
+            })
+            .map_err(io::Error::other)?;
+        watcher
+            .watch(&directory, RecursiveMode::NonRecursive)
+            .map_err(io::Error::other)?;
+        watched.watcher = Some(watcher);
+        Ok(watched)
+    }
+
+    /// A cheap handle to the current set.
+    pub fn load(&self) -> Arc<MaybeRegexSet> {
+        match self.current.read() {
+            Ok(current) => current.clone(),
+            Err(poisoned) => poisoned.into_inner().clone(),
+        }
+    }
+
+    pub fn reload(&self) -> io::Result<()> {
+        swap(&self.current, read_set(&self.path)?);
+        Ok(())
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+}
+
+fn swap(current: &RwLock<Arc<MaybeRegexSet>>, set: MaybeRegexSet) {
+    let set = Arc::new(set);
+    match current.write() {
+        Ok(mut current) => *current = set,
+        Err(poisoned) => *poisoned.into_inner() = set,
+    }
+}
+
+fn read_set(path: &Path) -> io::Result<MaybeRegexSet> {
+    let text = fs::read_to_string(path)?;
+    Ok(MaybeRegexSet::new(
+        text.lines()
+            .map(str::trim_end)
+            .filter(|line| !line.is_empty() && !line.starts_with('#')),
+    ))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::{thread, time::Duration};
+
+    #[test]
+    fn reloads_on_change() {
+        let path = std::env::temp_dir().join(format!("maybe-regex-watch-{}", std::process::id()));
+        fs::write(&path, "# errors only\nerror\n").unwrap();
+
+        let watched = WatchedPatternSet::watch(&path).unwrap();
+        let before = watched.load();
+        assert!(before.matches("error: disk full"));
+        assert!(!before.matches("warning: disk full"));
+
+        fs::write(&path, "error\nwarning\n-ignored\n").unwrap();
+        let mut reloaded = false;
+        for _ in 0..200 {
+            if watched.load().len() == 3 {
+                reloaded = true;
+                break;
+            }
+            thread::sleep(Duration::from_millis(10));
+        }
+        assert!(reloaded);
+        assert!(watched.load().matches("warning: disk full"));
+        // Handles taken earlier keep working with the old set
+        assert_eq!(before.len(), 1);
+
+        drop(watched);
+        fs::remove_file(&path).unwrap();
+    }
+}
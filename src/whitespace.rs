@@ -0,0 +1,70 @@
+use crate::{MaybeRegex, detect::literal_characters};
+
+impl MaybeRegex {
+    /// Treats any run of whitespace in the pattern as matching any run of whitespace, so a
+    /// pasted `foo  bar` matches `foo\nbar` after the text was re-wrapped. Whitespace inside
+    /// regex classes like `[ ]` is left alone.
+    ///
+    /// The pattern becomes a regex.
+    ///
+    /// ```
+    /// use maybe_regex::MaybeRegex;
+    ///
+    /// let pattern = MaybeRegex::new("disk  full").as_whitespace_insensitive();
+    /// assert!(pattern.matches("error: disk\n\tfull"));
+    /// assert!(!pattern.matches("error: diskfull"));
+    /// ```
+    pub fn as_whitespace_insensitive(self) -> Self {
+        self.wrapped(collapse_whitespace)
+    }
+}
+
+// Rewrites each run of literal whitespace in a regex into `\s+`. Regexes that don't parse are
+// left for the regex engine to report.
+fn collapse_whitespace(pattern: &str) -> String {
+    let Some(literals) = literal_characters(pattern) else {
+        return pattern.to_string();
+    };
+    let mut output = String::with_capacity(pattern.len());
+    let mut last = 0;
+    let mut run_end = None;
+    for (range, c) in literals {
+        if !c.is_whitespace() {
+            continue;
+        }
+        // Grouped so a repetition applied to the whitespace, as in ` +`, still parses
+        if run_end != Some(range.start) {
+            output.push_str(&pattern[last..range.start]);
+            output.push_str(r"(?:\s+)");
+        }
+        last = range.end;
+        run_end = Some(range.end);
+    }
+    output.push_str(&pattern[last..]);
+    output
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn collapses_whitespace_runs() {
+        let pasted = MaybeRegex::new("out of\n  memory").as_whitespace_insensitive();
+        assert!(pasted.matches("Out of memory") && pasted.matches("out\r\nof\tmemory"));
+        assert!(!pasted.matches("out ofmemory"));
+
+        let regex = MaybeRegex::new("^a +b$").as_whitespace_insensitive();
+        assert!(regex.is_regex() && regex.matches("a \n b") && !regex.matches("ab"));
+        assert!(
+            !MaybeRegex::new("a[ ]b")
+                .as_whitespace_insensitive()
+                .matches("a\nb")
+        );
+        assert!(
+            !MaybeRegex::new("-x y")
+                .as_whitespace_insensitive()
+                .matches("x\ty z")
+        );
+    }
+}